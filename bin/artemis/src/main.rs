@@ -15,11 +15,14 @@ use opensea_sudo_arb::types::{Action, Config, Event};
 use tracing::{info, Level};
 use tracing_subscriber::{filter, prelude::*};
 
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use artemis_core::engine::Engine;
-use artemis_core::types::{CollectorMap, ExecutorMap};
+use artemis_core::engine::{Engine, RestartPolicy};
+use artemis_core::types::{CollectorMap, ExecutorMap, Strategy};
+use artemis_core::utilities::snapshot::{SnapshotStore, SnapshotStrategy};
 
 /// CLI Options.
 #[derive(Parser, Debug)]
@@ -43,6 +46,10 @@ pub struct Args {
     /// Percentage of profit to pay in gas.
     #[arg(long)]
     pub bid_percentage: u64,
+
+    /// File to save the strategy state to, so that it can be restored on restart.
+    #[arg(long)]
+    pub snapshot_path: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -72,19 +79,14 @@ async fn main() -> Result<()> {
         api_key: args.opensea_api_key.clone(),
     });
 
-    // Set up engine.
-    let mut engine: Engine<Event, Action> = Engine::default();
-
     // Set up block collector.
     let block_collector = Box::new(BlockCollector::new(provider.clone()));
     let block_collector = CollectorMap::new(block_collector, Event::NewBlock);
-    engine.add_collector(Box::new(block_collector));
 
     // Set up opensea collector.
     let opensea_collector = Box::new(OpenseaOrderCollector::new(args.opensea_api_key));
     let opensea_collector =
-        CollectorMap::new(opensea_collector, |e| Event::OpenseaOrder(Box::new(e)));
-    engine.add_collector(Box::new(opensea_collector));
+        CollectorMap::new(opensea_collector, |e| Event::OpenseaEvent(Box::new(e)));
 
     // Set up opensea sudo arb strategy.
     let config = Config {
@@ -92,20 +94,39 @@ async fn main() -> Result<()> {
         bid_percentage: args.bid_percentage,
     };
     let strategy = OpenseaSudoArb::new(Arc::new(provider.clone()), opensea_client, config);
-    engine.add_strategy(Box::new(strategy));
+    let strategy: Box<dyn Strategy<Event, Action>> = match args.snapshot_path {
+        Some(path) => Box::new(SnapshotStrategy::new(strategy, SnapshotStore::new(path))),
+        None => Box::new(strategy),
+    };
 
     // Set up flashbots executor.
     let executor = Box::new(MempoolExecutor::new(provider.clone()));
     let executor = ExecutorMap::new(executor, |action| match action {
         Action::SubmitTx(tx) => Some(tx),
     });
-    engine.add_executor(Box::new(executor));
 
-    // Start engine.
-    if let Ok(mut set) = engine.run().await {
-        while let Some(res) = set.join_next().await {
-            info!("res: {:?}", res);
-        }
+    // Set up engine.
+    let engine: Engine<Event, Action> = Engine::builder()
+        .with_collector_restart_policy(RestartPolicy::exponential(
+            Duration::from_millis(500),
+            Duration::from_secs(30),
+        ))
+        .collector("blocks", Box::new(block_collector))
+        .collector("opensea-orders", Box::new(opensea_collector))
+        .strategy("opensea-sudo-arb", strategy)
+        .executor("mempool", Box::new(executor))
+        .build();
+
+    // Start engine, shutting it down gracefully on ctrl-c.
+    if let Ok(handle) = engine.run().await {
+        let shutdown = handle.shutdown_token();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                shutdown.cancel();
+            }
+        });
+        let summary = handle.wait().await;
+        info!("summary: {:?}", summary);
     }
     Ok(())
 }
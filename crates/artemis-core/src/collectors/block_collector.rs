@@ -1,35 +1,177 @@
+use super::transport::{Polling, Subscription};
 use crate::types::{Collector, CollectorStream};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use ethers::{
     prelude::Middleware,
     providers::PubsubClient,
-    types::{H256, U64},
+    types::{Block, Transaction, TransactionReceipt, H256, U256, U64},
 };
-use std::sync::Arc;
+use futures::{stream, Future, Stream};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
 use tokio_stream::StreamExt;
+use tracing::{error, warn};
 
 /// A collector that listens for new blocks, and generates a stream of
-/// [events](NewBlock) which contain the block number and hash.
-pub struct BlockCollector<M> {
+/// [events](NewBlock) which contain the block header, and depending on its
+/// [mode](BlockMode) the transactions and receipts of the block. By default
+/// it subscribes to new blocks, see [with_polling](BlockCollector::with_polling)
+/// for providers which do not support subscriptions.
+pub struct BlockCollector<M, T = Subscription> {
     provider: Arc<M>,
+    mode: BlockMode,
+    transport: T,
 }
 
-/// A new block event, containing the block number and hash.
-#[derive(Debug, Clone)]
+/// What a [BlockCollector](BlockCollector) includes in its events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockMode {
+    /// Only the block header, which comes with the subscription, or is fetched
+    /// for every block when polling.
+    #[default]
+    Header,
+    /// The block header and transactions, which are fetched for every block.
+    Transactions,
+    /// The block header, transactions and receipts, which are fetched for
+    /// every block. Receipts are fetched with `eth_getBlockReceipts`.
+    Receipts,
+}
+
+/// A new block event, containing the block header, and optionally its
+/// transactions and receipts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NewBlock {
     pub hash: H256,
     pub number: U64,
+    pub parent_hash: H256,
+    pub timestamp: U256,
+    pub gas_used: U256,
+    pub gas_limit: U256,
+    /// Base fee of the block, if it is post-London.
+    pub base_fee_per_gas: Option<U256>,
+    /// Base fee of the next block, predicted from the gas used by the block.
+    pub next_base_fee_per_gas: Option<U256>,
+    /// Transactions of the block, in the [Transactions](BlockMode::Transactions)
+    /// and [Receipts](BlockMode::Receipts) modes.
+    pub transactions: Option<Vec<Transaction>>,
+    /// Receipts of the block, in the [Receipts](BlockMode::Receipts) mode.
+    pub receipts: Option<Vec<TransactionReceipt>>,
+}
+
+impl NewBlock {
+    /// Returns the event for a block header, or `None` if the block is pending.
+    fn from_block<TX>(block: &Block<TX>) -> Option<Self> {
+        Some(Self {
+            hash: block.hash?,
+            number: block.number?,
+            parent_hash: block.parent_hash,
+            timestamp: block.timestamp,
+            gas_used: block.gas_used,
+            gas_limit: block.gas_limit,
+            base_fee_per_gas: block.base_fee_per_gas,
+            next_base_fee_per_gas: block.next_block_base_fee(),
+            transactions: None,
+            receipts: None,
+        })
+    }
 }
 
 impl<M> BlockCollector<M> {
     pub fn new(provider: Arc<M>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            mode: BlockMode::default(),
+            transport: Subscription,
+        }
+    }
+}
+
+impl<M, T> BlockCollector<M, T> {
+    pub fn with_mode(mut self, mode: BlockMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Detects reorgs by tracking the last `window` blocks of the canonical
+    /// chain. See [ReorgAwareBlockCollector](ReorgAwareBlockCollector).
+    pub fn with_reorg_detection(self, window: usize) -> ReorgAwareBlockCollector<M, T> {
+        ReorgAwareBlockCollector {
+            collector: self,
+            window,
+        }
+    }
+
+    /// Polls for new blocks every `interval` with `eth_newBlockFilter` and
+    /// `eth_getFilterChanges` instead of subscribing to them, so that the
+    /// collector works with any provider.
+    pub fn with_polling(self, interval: Duration) -> BlockCollector<M, Polling> {
+        BlockCollector {
+            provider: self.provider,
+            mode: self.mode,
+            transport: Polling::new(interval),
+        }
+    }
+}
+
+impl<M, T> BlockCollector<M, T>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    /// Fetches a block by hash, along with its transactions and receipts
+    /// depending on the mode.
+    async fn fetch_block(&self, hash: H256) -> Result<NewBlock> {
+        let not_found = || anyhow!("block {:?} not found", hash);
+        let pending = || anyhow!("block {:?} is pending", hash);
+        let mut new_block = match self.mode {
+            BlockMode::Header => {
+                let block = self.provider.get_block(hash).await?.ok_or_else(not_found)?;
+                NewBlock::from_block(&block).ok_or_else(pending)?
+            }
+            BlockMode::Transactions | BlockMode::Receipts => {
+                let block = self
+                    .provider
+                    .get_block_with_txs(hash)
+                    .await?
+                    .ok_or_else(not_found)?;
+                let mut new_block = NewBlock::from_block(&block).ok_or_else(pending)?;
+                new_block.transactions = Some(block.transactions);
+                new_block
+            }
+        };
+        if self.mode == BlockMode::Receipts {
+            let receipts = self.provider.get_block_receipts(new_block.number).await?;
+            new_block.receipts = Some(receipts);
+        }
+        Ok(new_block)
+    }
+
+    /// Fetches the blocks of a stream of hashes. Blocks which cannot be
+    /// fetched are dropped.
+    fn fetch_blocks<'a>(
+        &'a self,
+        hashes: impl Stream<Item = H256> + Send + 'a,
+    ) -> CollectorStream<'a, NewBlock>
+    where
+        T: Sync,
+    {
+        let stream = futures::StreamExt::filter_map(hashes, move |hash| async move {
+            match self.fetch_block(hash).await {
+                Ok(new_block) => Some(new_block),
+                Err(e) => {
+                    error!("error fetching block {:?}: {}", hash, e);
+                    None
+                }
+            }
+        });
+        Box::pin(stream)
     }
 }
 
 /// Implementation of the [Collector](Collector) trait for the [BlockCollector](BlockCollector).
 /// This implementation uses the [PubsubClient](PubsubClient) to subscribe to new blocks.
+/// Blocks whose transactions or receipts cannot be fetched are dropped.
 #[async_trait]
 impl<M> Collector<NewBlock> for BlockCollector<M>
 where
@@ -39,10 +181,182 @@ where
 {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, NewBlock>> {
         let stream = self.provider.subscribe_blocks().await?;
-        let stream = stream.filter_map(|block| match block.hash {
-            Some(hash) => block.number.map(|number| NewBlock { hash, number }),
-            None => None,
-        });
+        if self.mode == BlockMode::Header {
+            let stream = stream.filter_map(|block| NewBlock::from_block(&block));
+            return Ok(Box::pin(stream));
+        }
+
+        let hashes = stream.filter_map(|block| block.hash);
+        Ok(self.fetch_blocks(hashes))
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [BlockCollector](BlockCollector)
+/// with the [Polling](Polling) transport. This implementation installs a block
+/// filter and fetches every block it reports. Blocks which cannot be fetched
+/// are dropped.
+#[async_trait]
+impl<M> Collector<NewBlock> for BlockCollector<M, Polling>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, NewBlock>> {
+        let hashes = self
+            .provider
+            .watch_blocks()
+            .await?
+            .interval(self.transport.interval);
+        Ok(self.fetch_blocks(hashes))
+    }
+}
+
+/// A block collector which detects reorgs, and generates a stream of
+/// [events](BlockEvent) containing new blocks and reorgs. Blocks skipped by
+/// the subscription are fetched, so that every block of the canonical chain is
+/// emitted in order.
+pub struct ReorgAwareBlockCollector<M, T = Subscription> {
+    collector: BlockCollector<M, T>,
+    window: usize,
+}
+
+/// An event emitted by a [ReorgAwareBlockCollector](ReorgAwareBlockCollector).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlockEvent {
+    NewBlock(NewBlock),
+    Reorg(Reorg),
+}
+
+/// A reorg event, emitted when the canonical chain switches to a different
+/// fork. It is followed by a [NewBlock](BlockEvent::NewBlock) event for every
+/// block of the new chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reorg {
+    /// Number of blocks which were orphaned, capped at the size of the window.
+    pub depth: u64,
+    /// The orphaned blocks, in ascending order.
+    pub old: Vec<NewBlock>,
+    /// The blocks which replaced them, in ascending order.
+    pub new: Vec<NewBlock>,
+}
+
+/// Tracks a window of recent blocks of the canonical chain in order to detect
+/// reorgs.
+#[derive(Debug, Clone)]
+pub struct ReorgDetector {
+    window: usize,
+    blocks: VecDeque<NewBlock>,
+}
+
+impl ReorgDetector {
+    /// Creates a detector tracking the last `window` blocks, which is the
+    /// deepest reorg it can resolve.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            blocks: VecDeque::new(),
+        }
+    }
+
+    /// Adds a new block to the canonical chain. Blocks between the new block
+    /// and the tracked blocks are fetched with `fetch_block` by hash until the
+    /// common ancestor is found. Returns a [Reorg](BlockEvent::Reorg) event if
+    /// any tracked block was orphaned, followed by a new block event for every
+    /// block added to the chain.
+    pub async fn apply<F, Fut>(
+        &mut self,
+        block: NewBlock,
+        mut fetch_block: F,
+    ) -> Result<Vec<BlockEvent>>
+    where
+        F: FnMut(H256) -> Fut,
+        Fut: Future<Output = Result<NewBlock>>,
+    {
+        if self.blocks.iter().any(|known| known.hash == block.hash) {
+            return Ok(vec![]);
+        }
+
+        // Walk back from the new block until reaching a tracked block, or the
+        // bottom of the window if the reorg is deeper than it.
+        let lowest = self.blocks.front().map(|known| known.number);
+        let mut new = vec![block];
+        let ancestor = loop {
+            let first = new.last().unwrap();
+            if let Some(index) = self
+                .blocks
+                .iter()
+                .rposition(|known| known.hash == first.parent_hash)
+            {
+                break Some(index);
+            }
+            match lowest {
+                Some(lowest) if first.number > lowest => {
+                    let parent = fetch_block(first.parent_hash).await?;
+                    new.push(parent);
+                }
+                _ => break None,
+            }
+        };
+        new.reverse();
+
+        let old: Vec<NewBlock> = match ancestor {
+            Some(index) => self.blocks.drain(index + 1..).collect(),
+            None => self.blocks.drain(..).collect(),
+        };
+        let mut events = vec![];
+        if !old.is_empty() {
+            warn!("reorg of depth {} at block {}", old.len(), new[0].number);
+            events.push(BlockEvent::Reorg(Reorg {
+                depth: old.len() as u64,
+                old,
+                new: new.clone(),
+            }));
+        }
+        for block in new {
+            self.blocks.push_back(block.clone());
+            events.push(BlockEvent::NewBlock(block));
+        }
+        while self.blocks.len() > self.window {
+            self.blocks.pop_front();
+        }
+        Ok(events)
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [ReorgAwareBlockCollector](ReorgAwareBlockCollector). Blocks whose ancestors
+/// cannot be fetched are dropped.
+#[async_trait]
+impl<M, T> Collector<BlockEvent> for ReorgAwareBlockCollector<M, T>
+where
+    M: Middleware,
+    M::Error: 'static,
+    T: Send + Sync,
+    BlockCollector<M, T>: Collector<NewBlock>,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, BlockEvent>> {
+        let blocks = self.collector.get_event_stream().await?;
+        let detector = ReorgDetector::new(self.window);
+        let collector = &self.collector;
+
+        let stream = stream::unfold(
+            (blocks, detector, VecDeque::new()),
+            move |(mut blocks, mut detector, mut pending)| async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (blocks, detector, pending)));
+                    }
+                    let block = blocks.next().await?;
+                    match detector
+                        .apply(block, |hash| collector.fetch_block(hash))
+                        .await
+                    {
+                        Ok(events) => pending.extend(events),
+                        Err(e) => error!("error tracking new block: {}", e),
+                    }
+                }
+            },
+        );
         Ok(Box::pin(stream))
     }
 }
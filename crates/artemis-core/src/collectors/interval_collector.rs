@@ -0,0 +1,101 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::stream;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::time::{interval, sleep, MissedTickBehavior};
+
+use crate::types::{Collector, CollectorStream};
+
+/// A collector that emits a [Tick](Tick) at a fixed cadence, so that strategies
+/// can run periodic work without relying on new blocks as a clock. The first
+/// tick is emitted straight away.
+#[derive(Debug, Clone)]
+pub struct IntervalCollector {
+    period: Duration,
+    jitter: Duration,
+    missed_tick_policy: MissedTickPolicy,
+}
+
+/// A tick event, containing the time at which it was emitted and its sequence
+/// number, which starts at zero for every event stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tick {
+    pub at: SystemTime,
+    pub seq: u64,
+}
+
+/// What an [IntervalCollector](IntervalCollector) does when ticks are missed
+/// because the engine could not keep up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissedTickPolicy {
+    /// Emit the missed ticks straight away to catch up.
+    Burst,
+    /// Emit a tick straight away, then start a new cadence from there.
+    Delay,
+    /// Drop the missed ticks and resume on the original cadence.
+    #[default]
+    Skip,
+}
+
+impl From<MissedTickPolicy> for MissedTickBehavior {
+    fn from(policy: MissedTickPolicy) -> Self {
+        match policy {
+            MissedTickPolicy::Burst => MissedTickBehavior::Burst,
+            MissedTickPolicy::Delay => MissedTickBehavior::Delay,
+            MissedTickPolicy::Skip => MissedTickBehavior::Skip,
+        }
+    }
+}
+
+impl IntervalCollector {
+    pub fn new(period: Duration) -> Self {
+        Self {
+            period,
+            jitter: Duration::ZERO,
+            missed_tick_policy: MissedTickPolicy::default(),
+        }
+    }
+
+    /// Delays every tick by a random duration of up to `jitter`, e.g. to avoid
+    /// several bots polling an API in lockstep.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_missed_tick_policy(mut self, policy: MissedTickPolicy) -> Self {
+        self.missed_tick_policy = policy;
+        self
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [IntervalCollector](IntervalCollector).
+#[async_trait]
+impl Collector<Tick> for IntervalCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Tick>> {
+        if self.period.is_zero() {
+            return Err(anyhow!("interval collector period must be non-zero"));
+        }
+
+        let mut ticks = interval(self.period);
+        ticks.set_missed_tick_behavior(self.missed_tick_policy.into());
+        let jitter = self.jitter;
+
+        let stream = stream::unfold((ticks, 0), move |(mut ticks, seq)| async move {
+            ticks.tick().await;
+            if !jitter.is_zero() {
+                let delay = rand::thread_rng().gen_range(Duration::ZERO..=jitter);
+                sleep(delay).await;
+            }
+            let tick = Tick {
+                at: SystemTime::now(),
+                seq,
+            };
+            Some((tick, (ticks, seq + 1)))
+        });
+        Ok(Box::pin(stream))
+    }
+}
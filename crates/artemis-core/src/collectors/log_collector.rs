@@ -1,29 +1,282 @@
+use super::transport::{Polling, Subscription};
 use crate::types::{Collector, CollectorStream};
 use anyhow::Result;
 use async_trait::async_trait;
 use ethers::{
     prelude::Middleware,
     providers::PubsubClient,
-    types::{Filter, Log},
+    types::{Filter, Log, U256},
 };
-use std::sync::Arc;
-use tokio_stream::StreamExt;
+use futures::{future, stream, Stream, StreamExt};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::{interval, sleep, MissedTickBehavior};
+use tracing::{error, warn};
+
+/// The default number of blocks queried at once when backfilling logs.
+const DEFAULT_CHUNK_SIZE: u64 = 2000;
+
+/// The number of consecutive successful queries after which the chunk size is
+/// doubled again, up to its maximum.
+const GROW_AFTER: u32 = 3;
+
+/// The delay before retrying a failed backfill query.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
 
 /// A collector that listens for new blockchain event logs based on a [Filter](Filter),
-/// and generates a stream of [events](Log).
-pub struct LogCollector<M> {
+/// and generates a stream of [events](Log). By default it subscribes to new
+/// logs, see [with_polling](LogCollector::with_polling) for providers which do
+/// not support subscriptions.
+///
+/// With a [start block](LogCollector::with_start_block), the collector first
+/// backfills the historical logs from that block, and then hands over to the
+/// new logs without gaps or duplicates. Once it emitted logs, a restarted
+/// collector resumes after the last log it emitted instead.
+pub struct LogCollector<M, T = Subscription> {
     provider: Arc<M>,
     filter: Filter,
+    start_block: Option<u64>,
+    chunk_size: u64,
+    transport: T,
+    /// The block number and log index of the last log emitted.
+    last_emitted: Mutex<Option<(u64, U256)>>,
 }
 
 impl<M> LogCollector<M> {
     pub fn new(provider: Arc<M>, filter: Filter) -> Self {
-        Self { provider, filter }
+        Self {
+            provider,
+            filter,
+            start_block: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            transport: Subscription,
+            last_emitted: Mutex::new(None),
+        }
+    }
+}
+
+impl<M, T> LogCollector<M, T> {
+    /// Backfills the logs from `start_block` before emitting new logs. The
+    /// block range of the filter is ignored.
+    pub fn with_start_block(mut self, start_block: u64) -> Self {
+        self.start_block = Some(start_block);
+        self
+    }
+
+    /// Sets the maximum number of blocks queried at once when backfilling
+    /// logs. The chunk size is halved whenever the provider rejects a query
+    /// for covering too many blocks or logs, and grows back after successful
+    /// queries.
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Polls for new logs every `interval` with `eth_getLogs`, querying the
+    /// blocks produced since the last poll, instead of subscribing to them, so
+    /// that the collector works with any provider.
+    pub fn with_polling(self, interval: Duration) -> LogCollector<M, Polling> {
+        LogCollector {
+            provider: self.provider,
+            filter: self.filter,
+            start_block: self.start_block,
+            chunk_size: self.chunk_size,
+            transport: Polling::new(interval),
+            last_emitted: self.last_emitted,
+        }
     }
 }
 
+/// The number of blocks queried at once when backfilling logs, which adapts to
+/// the limits of the provider.
+#[derive(Debug, Clone, Copy)]
+struct ChunkSize {
+    current: u64,
+    max: u64,
+    /// Consecutive successful queries since the chunk size last changed.
+    successes: u32,
+}
+
+impl ChunkSize {
+    fn new(max: u64) -> Self {
+        Self {
+            current: max,
+            max,
+            successes: 0,
+        }
+    }
+
+    /// Halves the chunk size, returning false if it cannot shrink further.
+    fn shrink(&mut self) -> bool {
+        if self.current == 1 {
+            return false;
+        }
+        self.current /= 2;
+        self.successes = 0;
+        true
+    }
+
+    /// Records a successful query, doubling the chunk size after
+    /// [GROW_AFTER](GROW_AFTER) of them.
+    fn grow(&mut self) {
+        self.successes += 1;
+        if self.successes >= GROW_AFTER && self.current < self.max {
+            self.current = self.current.saturating_mul(2).min(self.max);
+            self.successes = 0;
+        }
+    }
+}
+
+impl<M, T> LogCollector<M, T>
+where
+    M: Middleware,
+    M::Error: 'static,
+    T: Sync,
+{
+    /// Fetches the logs of at most one chunk of blocks from `from_block`, and
+    /// up to `to_block`. The chunk size is halved until the provider accepts
+    /// the query. Returns the logs along with the last block they cover.
+    async fn fetch_chunk(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        chunk_size: &mut ChunkSize,
+    ) -> Result<(Vec<Log>, u64)> {
+        loop {
+            let end = to_block.min(from_block.saturating_add(chunk_size.current - 1));
+            let filter = self.filter.clone().from_block(from_block).to_block(end);
+            match self.provider.get_logs(&filter).await {
+                Ok(logs) => {
+                    chunk_size.grow();
+                    return Ok((logs, end));
+                }
+                Err(e) if is_range_error(&e.to_string()) && chunk_size.shrink() => {
+                    warn!("shrinking log chunk size to {}: {}", chunk_size.current, e);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Fetches the logs matching the filter from `from_block` up to `to_block`,
+    /// in chunks which adapt to the ranges the provider accepts like a backfill,
+    /// but returning the first error instead of retrying it. The block range
+    /// of the filter is ignored.
+    pub async fn get_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<Log>> {
+        let mut chunk_size = ChunkSize::new(self.chunk_size);
+        let mut logs = vec![];
+        let mut from_block = from_block;
+        while from_block <= to_block {
+            let (chunk, end) = self
+                .fetch_chunk(from_block, to_block, &mut chunk_size)
+                .await?;
+            logs.extend(chunk);
+            from_block = end + 1;
+        }
+        Ok(logs)
+    }
+
+    /// Streams the logs from `from_block` up to `to_block` in chunks. Failed
+    /// queries are retried, so that no block is skipped.
+    fn backfill(&self, from_block: u64, to_block: u64) -> impl Stream<Item = Log> + Send + '_ {
+        stream::unfold(
+            (from_block, ChunkSize::new(self.chunk_size)),
+            move |(from_block, mut chunk_size)| async move {
+                if from_block > to_block {
+                    return None;
+                }
+                loop {
+                    match self
+                        .fetch_chunk(from_block, to_block, &mut chunk_size)
+                        .await
+                    {
+                        Ok((logs, end)) => {
+                            return Some((stream::iter(logs), (end + 1, chunk_size)));
+                        }
+                        Err(e) => {
+                            error!("error fetching logs from block {}: {}", from_block, e);
+                            sleep(RETRY_DELAY).await;
+                        }
+                    }
+                }
+            },
+        )
+        .flatten()
+    }
+}
+
+impl<M, T> LogCollector<M, T>
+where
+    M: Send + Sync,
+    T: Sync,
+{
+    /// Returns the block to backfill the logs from: the block of the last log
+    /// emitted if the collector is restarted, or else the start block.
+    fn resume_block(&self) -> Option<u64> {
+        let last_emitted = *self.last_emitted.lock().unwrap();
+        last_emitted.map(|(block, _)| block).or(self.start_block)
+    }
+
+    /// Drops the logs emitted before the stream was created, and keeps track
+    /// of the last log emitted.
+    fn track<'a>(
+        &'a self,
+        stream: impl Stream<Item = Log> + Send + 'a,
+    ) -> CollectorStream<'a, Log> {
+        let last_emitted = *self.last_emitted.lock().unwrap();
+        let stream = stream
+            .filter(move |log| {
+                let emitted = matches!(
+                    (position(log), last_emitted),
+                    (Some(position), Some(last)) if position <= last
+                );
+                future::ready(!emitted)
+            })
+            .inspect(move |log| {
+                if let Some(position) = position(log) {
+                    *self.last_emitted.lock().unwrap() = Some(position);
+                }
+            });
+        Box::pin(stream)
+    }
+}
+
+/// Returns the block number and log index of a log which is included in a block.
+fn position(log: &Log) -> Option<(u64, U256)> {
+    let block = log.block_number?.as_u64();
+    Some((block, log.log_index.unwrap_or_default()))
+}
+
+/// Returns whether a `eth_getLogs` error means that the query covers too many
+/// blocks or logs. Providers word these errors differently, e.g. "block range
+/// too large" or "query returned more than 10000 results". Rate limiting errors
+/// such as "rate limit exceeded" do not match, since a smaller query would not
+/// fix them.
+fn is_range_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    let rate_limited = ["rate limit", "too many requests", "compute units"]
+        .iter()
+        .any(|pattern| error.contains(pattern));
+    let range_exceeded = [
+        "block range",
+        "range too large",
+        "range is too large",
+        "range is too wide",
+        "returned more than",
+        "too many results",
+        "response size",
+    ]
+    .iter()
+    .any(|pattern| error.contains(pattern));
+    range_exceeded && !rate_limited
+}
+
 /// Implementation of the [Collector](Collector) trait for the [LogCollector](LogCollector).
 /// This implementation uses the [PubsubClient](PubsubClient) to subscribe to new logs.
+/// When backfilling, the subscription is opened first, and the new logs of the
+/// backfilled blocks are skipped.
 #[async_trait]
 impl<M> Collector<Log> for LogCollector<M>
 where
@@ -33,7 +286,54 @@ where
 {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, Log>> {
         let stream = self.provider.subscribe_logs(&self.filter).await?;
-        let stream = stream.filter_map(Some);
-        Ok(Box::pin(stream))
+        let Some(start_block) = self.resume_block() else {
+            return Ok(self.track(stream));
+        };
+
+        let head = self.provider.get_block_number().await?.as_u64();
+        let stream = stream.filter(move |log| {
+            let backfilled = matches!(log.block_number, Some(block) if block.as_u64() <= head);
+            future::ready(!backfilled)
+        });
+        Ok(self.track(self.backfill(start_block, head).chain(stream)))
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [LogCollector](LogCollector)
+/// with the [Polling](Polling) transport. This implementation queries the logs
+/// of the blocks produced since the last poll, starting after the current block
+/// unless a start block is set or the collector is restarted.
+#[async_trait]
+impl<M> Collector<Log> for LogCollector<M, Polling>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Log>> {
+        let next_block = match self.resume_block() {
+            Some(start_block) => start_block,
+            None => self.provider.get_block_number().await?.as_u64() + 1,
+        };
+        let mut ticks = interval(self.transport.interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let stream = stream::unfold(
+            (ticks, next_block),
+            move |(mut ticks, next_block)| async move {
+                loop {
+                    ticks.tick().await;
+                    match self.provider.get_block_number().await {
+                        Ok(latest) if latest.as_u64() >= next_block => {
+                            let latest = latest.as_u64();
+                            let logs = self.backfill(next_block, latest);
+                            return Some((logs, (ticks, latest + 1)));
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("error polling block number: {}", e),
+                    }
+                }
+            },
+        );
+        Ok(self.track(stream.flatten()))
     }
 }
@@ -1,21 +1,196 @@
 use async_trait::async_trait;
 
-use ethers::{prelude::Middleware, providers::PubsubClient, types::Transaction};
-use futures::StreamExt;
-use std::sync::Arc;
+use ethers::{
+    abi::{Abi, Function, Token},
+    prelude::Middleware,
+    providers::PubsubClient,
+    types::{Address, Selector, Transaction, U256},
+};
+use futures::{future, Stream, StreamExt};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
+use super::transport::{Polling, Subscription};
 use crate::types::{Collector, CollectorStream};
 use anyhow::Result;
 
 /// A collector that listens for new transactions in the mempool, and generates a stream of
-/// [events](Transaction) which contain the transaction.
-pub struct MempoolCollector<M> {
+/// [events](Transaction) which contain the transaction. By default it subscribes to new
+/// transactions, see [with_polling](MempoolCollector::with_polling) for providers which
+/// do not support subscriptions.
+///
+/// Transactions which do not match the collector's [filter](MempoolFilter) are dropped,
+/// and with a [decoder](CallDecoder) the collector emits their decoded calldata.
+pub struct MempoolCollector<M, T = Subscription> {
     provider: Arc<M>,
+    filter: MempoolFilter,
+    transport: T,
+}
+
+/// A filter on the transactions emitted by a [MempoolCollector](MempoolCollector). An
+/// empty filter matches every transaction.
+#[derive(Debug, Clone, Default)]
+pub struct MempoolFilter {
+    to: HashSet<Address>,
+    selectors: HashSet<Selector>,
+    min_value: U256,
+    min_gas_price: U256,
+}
+
+impl MempoolFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches transactions sent to one of `addresses`.
+    pub fn with_to(mut self, addresses: impl IntoIterator<Item = Address>) -> Self {
+        self.to.extend(addresses);
+        self
+    }
+
+    /// Only matches transactions whose calldata starts with one of `selectors`.
+    pub fn with_selectors(mut self, selectors: impl IntoIterator<Item = Selector>) -> Self {
+        self.selectors.extend(selectors);
+        self
+    }
+
+    /// Only matches transactions sending at least `min_value` wei.
+    pub fn with_min_value(mut self, min_value: U256) -> Self {
+        self.min_value = min_value;
+        self
+    }
+
+    /// Only matches transactions paying at least `min_gas_price` wei per gas. The
+    /// max fee per gas is used for EIP-1559 transactions.
+    pub fn with_min_gas_price(mut self, min_gas_price: U256) -> Self {
+        self.min_gas_price = min_gas_price;
+        self
+    }
+
+    /// Returns whether a transaction matches the filter.
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        if !self.to.is_empty() && !matches!(tx.to, Some(to) if self.to.contains(&to)) {
+            return false;
+        }
+        if !self.selectors.is_empty() {
+            let selector: Option<Selector> =
+                tx.input.get(..4).and_then(|input| input.try_into().ok());
+            if !matches!(selector, Some(selector) if self.selectors.contains(&selector)) {
+                return false;
+            }
+        }
+        let gas_price = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+        tx.value >= self.min_value && gas_price >= self.min_gas_price
+    }
+}
+
+/// Decodes transaction calldata against the functions of registered ABIs, by
+/// selector. If several functions share a selector, the first one registered
+/// is used.
+#[derive(Debug, Clone, Default)]
+pub struct CallDecoder {
+    functions: HashMap<Selector, Function>,
+}
+
+/// A function call decoded by a [CallDecoder](CallDecoder).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedCall {
+    pub name: String,
+    /// The function signature, e.g. `transfer(address,uint256)`.
+    pub signature: String,
+    pub inputs: Vec<Token>,
+}
+
+/// A transaction emitted by a [DecodingMempoolCollector](DecodingMempoolCollector),
+/// along with its decoded calldata, or `None` if it does not call a registered
+/// function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedTransaction {
+    pub transaction: Transaction,
+    pub call: Option<DecodedCall>,
+}
+
+impl CallDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the functions of an ABI.
+    pub fn with_abi(mut self, abi: &Abi) -> Self {
+        for function in abi.functions() {
+            self.functions
+                .entry(function.short_signature())
+                .or_insert_with(|| function.clone());
+        }
+        self
+    }
+
+    /// Decodes calldata, or returns `None` if it does not call a registered
+    /// function or its arguments cannot be decoded.
+    pub fn decode(&self, input: &[u8]) -> Option<DecodedCall> {
+        let selector: Selector = input.get(..4)?.try_into().ok()?;
+        let function = self.functions.get(&selector)?;
+        let inputs = function.decode_input(&input[4..]).ok()?;
+        Some(DecodedCall {
+            name: function.name.clone(),
+            signature: function.signature(),
+            inputs,
+        })
+    }
 }
 
 impl<M> MempoolCollector<M> {
     pub fn new(provider: Arc<M>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            filter: MempoolFilter::default(),
+            transport: Subscription,
+        }
+    }
+}
+
+impl<M, T> MempoolCollector<M, T> {
+    /// Only emits the transactions matching `filter`.
+    pub fn with_filter(mut self, filter: MempoolFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Emits the transactions along with their calldata decoded by `decoder`.
+    /// See [DecodingMempoolCollector](DecodingMempoolCollector).
+    pub fn with_decoder(self, decoder: CallDecoder) -> DecodingMempoolCollector<M, T> {
+        DecodingMempoolCollector {
+            collector: self,
+            decoder,
+        }
+    }
+
+    /// Polls for new transactions every `interval` with `eth_newPendingTransactionFilter`
+    /// and `eth_getFilterChanges` instead of subscribing to them, so that the collector
+    /// works with any provider.
+    pub fn with_polling(self, interval: Duration) -> MempoolCollector<M, Polling> {
+        MempoolCollector {
+            provider: self.provider,
+            filter: self.filter,
+            transport: Polling::new(interval),
+        }
+    }
+
+    /// Drops the transactions which could not be fetched or do not match the filter.
+    fn filter_transactions<'a, E>(
+        &'a self,
+        stream: impl Stream<Item = Result<Transaction, E>> + Send + 'a,
+    ) -> CollectorStream<'a, Transaction>
+    where
+        M: Send + Sync,
+        T: Sync,
+    {
+        let stream = stream
+            .filter_map(move |res| future::ready(res.ok().filter(|tx| self.filter.matches(tx))));
+        Box::pin(stream)
     }
 }
 
@@ -31,7 +206,52 @@ where
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, Transaction>> {
         let stream = self.provider.subscribe_pending_txs().await?;
         let stream = stream.transactions_unordered(256);
-        let stream = stream.filter_map(|res| async move { res.ok() });
+        Ok(self.filter_transactions(stream))
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [MempoolCollector](MempoolCollector)
+/// with the [Polling](Polling) transport. This implementation installs a pending
+/// transaction filter and fetches every transaction it reports.
+#[async_trait]
+impl<M> Collector<Transaction> for MempoolCollector<M, Polling>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Transaction>> {
+        let stream = self
+            .provider
+            .watch_pending_transactions()
+            .await?
+            .interval(self.transport.interval);
+        let stream = stream.transactions_unordered(256);
+        Ok(self.filter_transactions(stream))
+    }
+}
+
+/// A mempool collector which decodes the calldata of the transactions it emits,
+/// and generates a stream of [events](DecodedTransaction).
+pub struct DecodingMempoolCollector<M, T = Subscription> {
+    collector: MempoolCollector<M, T>,
+    decoder: CallDecoder,
+}
+
+/// Implementation of the [Collector](Collector) trait for the
+/// [DecodingMempoolCollector](DecodingMempoolCollector).
+#[async_trait]
+impl<M, T> Collector<DecodedTransaction> for DecodingMempoolCollector<M, T>
+where
+    M: Send + Sync,
+    T: Send + Sync,
+    MempoolCollector<M, T>: Collector<Transaction>,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, DecodedTransaction>> {
+        let stream = self.collector.get_event_stream().await?;
+        let stream = stream.map(move |transaction| DecodedTransaction {
+            call: self.decoder.decode(&transaction.input),
+            transaction,
+        });
         Ok(Box::pin(stream))
     }
 }
@@ -1,32 +1,278 @@
+use crate::engine::RestartPolicy;
 use crate::types::{Collector, CollectorStream};
 use anyhow::Result;
 use async_trait::async_trait;
-use mev_share::sse::{Event, EventClient};
+use ethers::{
+    types::{Address, Selector, H256, I256, U256},
+    utils::keccak256,
+};
+use futures::stream;
+use mev_share::sse::{client::SseError, Event, EventClient, EventTransactionLog};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio_stream::StreamExt;
+use tracing::{error, warn};
+
+/// The MEV-Share SSE endpoint on mainnet.
+pub const MAINNET_ENDPOINT: &str = "https://mev-share.flashbots.net";
 
 /// A collector that streams from MEV-Share SSE endpoint
 /// and generates [events](Event), which return tx hash, logs, and bundled txs.
+///
+/// The collector reconnects with a backoff whenever the stream ends, errors or
+/// fails to connect, and skips the events it cannot parse, which are counted in
+/// its [stats](MevShareStats). Events can be turned into [hints](MevShareHint)
+/// which decode the most common hints, e.g. with
+/// [CollectorExt::map](crate::types::CollectorExt::map).
 pub struct MevShareCollector {
     mevshare_sse_url: String,
+    reconnect_policy: RestartPolicy,
+    stats: MevShareStats,
+}
+
+/// Counters shared by a [MevShareCollector](MevShareCollector) and the streams
+/// it creates.
+#[derive(Debug, Clone, Default)]
+pub struct MevShareStats {
+    parse_errors: Arc<AtomicU64>,
+    reconnects: Arc<AtomicU64>,
+}
+
+impl MevShareStats {
+    /// Number of events which could not be parsed.
+    pub fn parse_errors(&self) -> u64 {
+        self.parse_errors.load(Ordering::Relaxed)
+    }
+
+    /// Number of times the collector reconnected to the endpoint.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
 }
 
 impl MevShareCollector {
     pub fn new(mevshare_sse_url: String) -> Self {
-        Self { mevshare_sse_url }
+        Self {
+            mevshare_sse_url,
+            reconnect_policy: RestartPolicy::exponential(
+                Duration::from_secs(1),
+                Duration::from_secs(60),
+            ),
+            stats: MevShareStats::default(),
+        }
+    }
+
+    /// Creates a collector streaming from the [mainnet endpoint](MAINNET_ENDPOINT).
+    pub fn mainnet() -> Self {
+        Self::new(MAINNET_ENDPOINT.to_string())
+    }
+
+    /// Sets the policy used to reconnect when the stream ends or errors.
+    /// Consecutive reconnections are counted until an event is received, and
+    /// the stream ends once the policy gives up.
+    pub fn with_reconnect_policy(mut self, policy: RestartPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+
+    /// Returns a handle to the counters of the collector, which can be read
+    /// after the collector is added to an engine.
+    pub fn stats(&self) -> MevShareStats {
+        self.stats.clone()
     }
 }
 
 /// Implementation of the [Collector](Collector) trait for the
-/// [MevShareCollector](MevShareCollector).
+/// [MevShareCollector](MevShareCollector). An error is returned if the first
+/// connection fails, later connections are retried by the stream itself.
 #[async_trait]
 impl Collector<Event> for MevShareCollector {
     async fn get_event_stream(&self) -> Result<CollectorStream<'_, Event>> {
         let client = EventClient::default();
-        let stream = client.events(&self.mevshare_sse_url).await.unwrap();
-        let stream = stream.filter_map(|event| match event {
-            Ok(evt) => Some(evt),
-            Err(_) => None,
-        });
+        let events = client.events(&self.mevshare_sse_url).await?;
+
+        let stream = stream::unfold(
+            (client, Some(events), 0),
+            move |(client, mut events, mut reconnects)| async move {
+                loop {
+                    let Some(stream) = events.as_mut() else {
+                        let Some(backoff) = self.reconnect_policy.backoff(reconnects) else {
+                            error!(
+                                "mev-share collector stopped after {} reconnects",
+                                reconnects
+                            );
+                            return None;
+                        };
+                        tokio::time::sleep(backoff).await;
+                        reconnects += 1;
+                        self.stats.reconnects.fetch_add(1, Ordering::Relaxed);
+                        match client.events(&self.mevshare_sse_url).await {
+                            Ok(stream) => events = Some(stream),
+                            Err(e) => error!("error connecting to mev-share: {}", e),
+                        }
+                        continue;
+                    };
+                    match stream.next().await {
+                        Some(Ok(event)) => return Some((event, (client, events, 0))),
+                        Some(Err(SseError::SerdeJsonError(e))) => {
+                            self.stats.parse_errors.fetch_add(1, Ordering::Relaxed);
+                            warn!("error parsing mev-share event: {}", e);
+                        }
+                        Some(Err(e)) => {
+                            warn!("mev-share stream error: {}, reconnecting", e);
+                            events = None;
+                        }
+                        None => {
+                            warn!("mev-share stream ended, reconnecting");
+                            events = None;
+                        }
+                    }
+                }
+            },
+        );
         Ok(Box::pin(stream))
     }
 }
+
+/// A MEV-Share [event](Event) along with the hints it contains, decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MevShareHint {
+    pub hash: H256,
+    pub kind: HintKind,
+    /// Function selectors of the transactions, for those which share them.
+    pub selectors: Vec<Selector>,
+    /// Uniswap V2 and V3 swaps, decoded from the logs.
+    pub swaps: Vec<SwapHint>,
+    pub event: Event,
+}
+
+/// Whether a MEV-Share event is a single transaction or a bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HintKind {
+    Transaction,
+    /// A bundle, i.e. an event with several transactions.
+    Bundle,
+}
+
+/// A swap decoded from a Uniswap V2 or V3 `Swap` log. The direction and
+/// amounts are only known if the log data is shared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapHint {
+    pub pool: Address,
+    pub protocol: SwapProtocol,
+    /// Whether token0 is swapped for token1.
+    pub zero_for_one: Option<bool>,
+    /// Amount of tokens sent to the pool.
+    pub amount_in: Option<U256>,
+    /// Amount of tokens received from the pool.
+    pub amount_out: Option<U256>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapProtocol {
+    UniswapV2,
+    UniswapV3,
+}
+
+impl From<Event> for MevShareHint {
+    fn from(event: Event) -> Self {
+        let kind = if event.transactions.len() > 1 {
+            HintKind::Bundle
+        } else {
+            HintKind::Transaction
+        };
+        let selectors = event
+            .transactions
+            .iter()
+            .filter_map(|tx| tx.function_selector.as_ref().map(|selector| selector.0))
+            .collect();
+        let v2_topic = H256(keccak256(
+            "Swap(address,uint256,uint256,uint256,uint256,address)",
+        ));
+        let v3_topic = H256(keccak256(
+            "Swap(address,address,int256,int256,uint160,uint128,int24)",
+        ));
+        let swaps = event
+            .logs
+            .iter()
+            .filter_map(|log| match log.topics.first() {
+                Some(topic) if *topic == v2_topic => Some(SwapHint::v2(log)),
+                Some(topic) if *topic == v3_topic => Some(SwapHint::v3(log)),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            hash: event.hash,
+            kind,
+            selectors,
+            swaps,
+            event,
+        }
+    }
+}
+
+impl SwapHint {
+    /// Decodes a V2 swap, whose data holds `amount0In`, `amount1In`,
+    /// `amount0Out` and `amount1Out`.
+    fn v2(log: &EventTransactionLog) -> Self {
+        let mut swap = Self::new(log.address, SwapProtocol::UniswapV2);
+        if let [amount0_in, amount1_in, amount0_out, amount1_out] = words(&log.data)[..] {
+            let zero_for_one = !amount0_in.is_zero();
+            swap.zero_for_one = Some(zero_for_one);
+            if zero_for_one {
+                swap.amount_in = Some(amount0_in);
+                swap.amount_out = Some(amount1_out);
+            } else {
+                swap.amount_in = Some(amount1_in);
+                swap.amount_out = Some(amount0_out);
+            }
+        }
+        swap
+    }
+
+    /// Decodes a V3 swap, whose data holds the signed `amount0` and `amount1`
+    /// deltas of the pool, followed by its new price, liquidity and tick.
+    fn v3(log: &EventTransactionLog) -> Self {
+        let mut swap = Self::new(log.address, SwapProtocol::UniswapV3);
+        if let [amount0, amount1, _, _, _] = words(&log.data)[..] {
+            let (amount0, amount1) = (I256::from_raw(amount0), I256::from_raw(amount1));
+            let zero_for_one = amount0.is_positive();
+            let (amount_in, amount_out) = if zero_for_one {
+                (amount0, amount1)
+            } else {
+                (amount1, amount0)
+            };
+            swap.zero_for_one = Some(zero_for_one);
+            swap.amount_in = Some(amount_in.unsigned_abs());
+            swap.amount_out = Some(amount_out.unsigned_abs());
+        }
+        swap
+    }
+
+    fn new(pool: Address, protocol: SwapProtocol) -> Self {
+        Self {
+            pool,
+            protocol,
+            zero_for_one: None,
+            amount_in: None,
+            amount_out: None,
+        }
+    }
+}
+
+/// Splits log data into 32-byte words, or returns no words if the data is
+/// not word-aligned.
+fn words(data: &[u8]) -> Vec<U256> {
+    let words = data.chunks_exact(32);
+    if !words.remainder().is_empty() {
+        return vec![];
+    }
+    words.map(U256::from_big_endian).collect()
+}
@@ -5,13 +5,30 @@
 /// This collector listens to a stream of new blocks.
 pub mod block_collector;
 
+/// This collector emits ticks at a fixed cadence.
+pub mod interval_collector;
+
 /// This collector listens to a stream of new event logs.
 pub mod log_collector;
 
 /// This collector listens to a stream of new pending transactions.
 pub mod mempool_collector;
 
+/// This collector listens to Chainlink price feeds.
+pub mod oracle_collector;
+
 /// This collector listens to a stream of new Opensea orders.
 pub mod opensea_order_collector;
 
 pub mod mevshare_collector;
+
+/// This collector diffs periodic snapshots of the transaction pool.
+pub mod txpool_collector;
+
+/// This collector replays events recorded by an
+/// [EventRecorder](crate::utilities::event_recorder::EventRecorder).
+pub mod replay_collector;
+
+/// This module contains the transports of the block, log and mempool
+/// collectors, which either subscribe to new data or poll for it.
+pub mod transport;
@@ -1,52 +1,143 @@
 use crate::types::{Collector, CollectorStream};
 use anyhow::Result;
 use async_trait::async_trait;
+use futures::stream::select_all;
 use opensea_stream::{
     client,
-    schema::{self, ItemListedData},
+    schema::{
+        self, CollectionOfferData, ItemCancelledData, ItemListedData, ItemReceivedBidData,
+        ItemSoldData,
+    },
     subscribe_to, Collection, Network,
 };
+use serde::{Deserialize, Serialize};
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 
 /// A collector that listens for new orders on OpenSea, and generates a stream of
-/// [events](OpenseaOrder) which contain the order.
-#[derive(Default)]
+/// [events](OpenseaEvent) which contain listings, cancellations, sales, item bids
+/// and collection offers. By default it listens to every collection on mainnet.
 pub struct OpenseaOrderCollector {
     api_key: String,
+    network: Network,
+    collections: Vec<String>,
+}
+
+impl Default for OpenseaOrderCollector {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
 }
 
 impl OpenseaOrderCollector {
     pub fn new(api_key: String) -> Self {
-        Self { api_key }
+        Self {
+            api_key,
+            network: Network::Mainnet,
+            collections: vec![],
+        }
+    }
+
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Only listens to the collections with the given slugs, e.g. `boredapeyachtclub`.
+    pub fn with_collections(mut self, slugs: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.collections.extend(slugs.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns the collections to subscribe to, i.e. every collection unless
+    /// some slugs were given.
+    pub fn subscriptions(&self) -> Vec<Collection> {
+        if self.collections.is_empty() {
+            vec![Collection::All]
+        } else {
+            self.collections
+                .iter()
+                .map(|slug| Collection::Collection(slug.clone()))
+                .collect()
+        }
+    }
+}
+
+/// An event emitted by an [OpenseaOrderCollector](OpenseaOrderCollector).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum OpenseaEvent {
+    /// A new listing of an item.
+    Listing(ItemListedData),
+    /// A listing or offer was cancelled.
+    Cancellation(ItemCancelledData),
+    /// An item was sold, which fills its listing.
+    Sale(ItemSoldData),
+    /// A new bid on an item.
+    ItemBid(ItemReceivedBidData),
+    /// A new offer on any item of a collection.
+    CollectionOffer(CollectionOfferData),
+}
+
+impl OpenseaEvent {
+    /// Returns the event for a stream payload, or `None` for the payloads which
+    /// are not collected, e.g. transfers and metadata updates.
+    pub fn from_payload(payload: schema::Payload) -> Option<Self> {
+        match payload {
+            schema::Payload::ItemListed(listing) => Some(Self::Listing(listing)),
+            schema::Payload::ItemCancelled(cancellation) => Some(Self::Cancellation(cancellation)),
+            schema::Payload::ItemSold(sale) => Some(Self::Sale(sale)),
+            schema::Payload::ItemReceivedBid(bid) => Some(Self::ItemBid(bid)),
+            schema::Payload::CollectionOffer(offer) => Some(Self::CollectionOffer(offer)),
+            _ => None,
+        }
     }
 }
 
 /// A new order event, containing the internal order.
+#[deprecated(note = "the collector now emits OpenseaEvent, whose Listing variant holds the order")]
 #[derive(Debug, Clone)]
 pub struct OpenseaOrder {
     pub listing: ItemListedData,
 }
 
-/// Implementation of the [Collector](Collector) trait for the [OpenseaOrderCollector](OpenseaOrderCollector).
-#[async_trait]
-impl Collector<OpenseaOrder> for OpenseaOrderCollector {
-    async fn get_event_stream(&self) -> Result<CollectorStream<'_, OpenseaOrder>> {
-        let mut client = client(Network::Mainnet, &self.api_key).await;
+#[allow(deprecated)]
+impl OpenseaOrder {
+    /// Returns the order of a listing event, or `None` for the other events.
+    pub fn from_event(event: OpenseaEvent) -> Option<Self> {
+        match event {
+            OpenseaEvent::Listing(listing) => Some(Self { listing }),
+            _ => None,
+        }
+    }
+}
 
-        let collection = Collection::All;
+#[allow(deprecated)]
+impl From<OpenseaOrder> for OpenseaEvent {
+    fn from(order: OpenseaOrder) -> Self {
+        Self::Listing(order.listing)
+    }
+}
 
-        let (_, subscription) = subscribe_to(&mut client, collection).await?;
+/// Implementation of the [Collector](Collector) trait for the [OpenseaOrderCollector](OpenseaOrderCollector).
+/// Each collection is subscribed to separately, and their events are merged.
+#[async_trait]
+impl Collector<OpenseaEvent> for OpenseaOrderCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, OpenseaEvent>> {
+        let mut client = client(self.network, &self.api_key).await;
 
-        let stream = BroadcastStream::new(subscription);
+        let mut streams = vec![];
+        for collection in self.subscriptions() {
+            let (_, subscription) = subscribe_to(&mut client, collection).await?;
+            streams.push(BroadcastStream::new(subscription));
+        }
 
-        let stream = stream.filter_map(|event| {
+        let stream = select_all(streams).filter_map(|event| {
             let event = event.ok()?.into_custom_payload()?;
-            if let schema::Payload::ItemListed(listing) = event.payload {
-                Some(OpenseaOrder { listing })
-            } else {
-                None
-            }
+            OpenseaEvent::from_payload(event.payload)
         });
 
         Ok(Box::pin(stream))
@@ -0,0 +1,158 @@
+use super::log_collector::LogCollector;
+use super::transport::{Polling, Subscription};
+use crate::types::{Collector, CollectorStream};
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::{
+    types::{Address, Filter, Log, I256, U256},
+    utils::format_units,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_stream::StreamExt;
+use tracing::warn;
+
+/// The event emitted by Chainlink aggregators when a new answer is reported.
+const ANSWER_UPDATED: &str = "AnswerUpdated(int256,uint256,uint256)";
+
+/// The default heartbeat of a [PriceFeed](PriceFeed).
+const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(3600);
+
+/// A collector that watches Chainlink price feeds, and generates a stream of
+/// [events](PriceUpdate) containing the new prices. It is built on a
+/// [LogCollector](LogCollector) watching the `AnswerUpdated` logs of the
+/// aggregators, and supports the same transports and backfill.
+pub struct OracleCollector<M, T = Subscription> {
+    collector: LogCollector<M, T>,
+    feeds: HashMap<Address, PriceFeed>,
+}
+
+/// A Chainlink price feed watched by an [OracleCollector](OracleCollector).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceFeed {
+    /// The aggregator emitting the answers. Note that this is not the proxy
+    /// contract which is usually queried, see `aggregator()` on the proxy.
+    pub aggregator: Address,
+    /// The token priced by the feed.
+    pub token: Address,
+    /// The decimals of the answers.
+    pub decimals: u8,
+    /// The maximum time between two answers, after which a price is stale.
+    pub heartbeat: Duration,
+}
+
+impl PriceFeed {
+    pub fn new(aggregator: Address, token: Address, decimals: u8) -> Self {
+        Self {
+            aggregator,
+            token,
+            decimals,
+            heartbeat: DEFAULT_HEARTBEAT,
+        }
+    }
+
+    pub fn with_heartbeat(mut self, heartbeat: Duration) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+}
+
+/// A new price reported by a [PriceFeed](PriceFeed).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceUpdate {
+    pub token: Address,
+    /// The answer of the feed, normalised by its decimals, in the feed's quote
+    /// currency.
+    pub price: f64,
+    pub round_id: U256,
+    /// Unix timestamp at which the answer was reported.
+    pub updated_at: u64,
+    /// Whether the answer is older than the feed's heartbeat, e.g. because it
+    /// was backfilled.
+    pub stale: bool,
+}
+
+impl<M> OracleCollector<M> {
+    pub fn new(provider: Arc<M>, feeds: impl IntoIterator<Item = PriceFeed>) -> Self {
+        let feeds: HashMap<Address, PriceFeed> = feeds
+            .into_iter()
+            .map(|feed| (feed.aggregator, feed))
+            .collect();
+        let filter = Filter::new()
+            .address(feeds.keys().copied().collect::<Vec<_>>())
+            .event(ANSWER_UPDATED);
+        Self {
+            collector: LogCollector::new(provider, filter),
+            feeds,
+        }
+    }
+}
+
+impl<M, T> OracleCollector<M, T> {
+    /// Backfills the answers from `start_block`, see
+    /// [LogCollector::with_start_block](LogCollector::with_start_block).
+    pub fn with_start_block(mut self, start_block: u64) -> Self {
+        self.collector = self.collector.with_start_block(start_block);
+        self
+    }
+
+    /// Polls for new answers every `interval`, see
+    /// [LogCollector::with_polling](LogCollector::with_polling).
+    pub fn with_polling(self, interval: Duration) -> OracleCollector<M, Polling> {
+        OracleCollector {
+            collector: self.collector.with_polling(interval),
+            feeds: self.feeds,
+        }
+    }
+
+    /// Decodes an `AnswerUpdated` log, whose topics hold the answer and round
+    /// id, and whose data holds the update timestamp. Returns `None` for logs
+    /// of unknown feeds and invalid answers.
+    fn decode(&self, log: &Log) -> Option<PriceUpdate> {
+        let feed = self.feeds.get(&log.address)?;
+        let [_, current, round_id] = log.topics[..] else {
+            return None;
+        };
+        let answer = I256::from_raw(U256::from_big_endian(current.as_bytes()));
+        if !answer.is_positive() || log.data.len() != 32 {
+            warn!("invalid answer {} from feed {:?}", answer, feed.aggregator);
+            return None;
+        }
+        let price = format_units(answer.into_raw(), feed.decimals as u32)
+            .ok()?
+            .parse()
+            .ok()?;
+
+        let updated_at = U256::from_big_endian(&log.data).low_u64();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Some(PriceUpdate {
+            token: feed.token,
+            price,
+            round_id: U256::from_big_endian(round_id.as_bytes()),
+            updated_at,
+            stale: now.saturating_sub(updated_at) > feed.heartbeat.as_secs(),
+        })
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [OracleCollector](OracleCollector).
+#[async_trait]
+impl<M, T> Collector<PriceUpdate> for OracleCollector<M, T>
+where
+    M: Send + Sync,
+    T: Send + Sync,
+    LogCollector<M, T>: Collector<Log>,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, PriceUpdate>> {
+        let stream = self.collector.get_event_stream().await?;
+        let stream = stream.filter_map(move |log| self.decode(&log));
+        Ok(Box::pin(stream))
+    }
+}
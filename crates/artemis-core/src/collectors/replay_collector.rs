@@ -0,0 +1,92 @@
+use std::{marker::PhantomData, path::PathBuf, time::Duration};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::stream;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    types::{Collector, CollectorStream},
+    utilities::event_recorder::RecordedEvent,
+};
+
+/// A collector that replays the events recorded by an
+/// [EventRecorder](crate::utilities::event_recorder::EventRecorder), in the
+/// order in which they were recorded. The event stream ends once every event
+/// has been replayed, so an engine running only replay collectors stops on its
+/// own.
+pub struct ReplayCollector<E> {
+    path: PathBuf,
+    pace: ReplayPace,
+    _event: PhantomData<fn() -> E>,
+}
+
+/// How fast a [ReplayCollector](ReplayCollector) replays events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReplayPace {
+    /// Emit every event as soon as possible.
+    #[default]
+    Immediate,
+    /// Wait between events for as long as elapsed between them when they were
+    /// recorded.
+    Recorded,
+}
+
+impl<E> ReplayCollector<E> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            pace: ReplayPace::default(),
+            _event: PhantomData,
+        }
+    }
+
+    pub fn with_pace(mut self, pace: ReplayPace) -> Self {
+        self.pace = pace;
+        self
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [ReplayCollector](ReplayCollector).
+/// The whole file is read and parsed upfront, so that a malformed recording
+/// fails before any event is replayed.
+#[async_trait]
+impl<E> Collector<E> for ReplayCollector<E>
+where
+    E: DeserializeOwned + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let contents = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("error reading recording {}", self.path.display()))?;
+
+        let mut events = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let recorded: RecordedEvent<E> = serde_json::from_str(line).with_context(|| {
+                format!(
+                    "error parsing line {} of {}",
+                    index + 1,
+                    self.path.display()
+                )
+            })?;
+            events.push(recorded);
+        }
+
+        let pace = self.pace;
+        let stream = stream::unfold(
+            (events.into_iter(), None),
+            move |(mut events, previous): (_, Option<u64>)| async move {
+                let recorded = events.next()?;
+                if let (ReplayPace::Recorded, Some(previous)) = (pace, previous) {
+                    let gap = recorded.timestamp_ms.saturating_sub(previous);
+                    tokio::time::sleep(Duration::from_millis(gap)).await;
+                }
+                Some((recorded.event, (events, Some(recorded.timestamp_ms))))
+            },
+        );
+        Ok(Box::pin(stream))
+    }
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+/// The default transport of the block, log and mempool collectors, which
+/// subscribes to new data through a [PubsubClient](ethers::providers::PubsubClient),
+/// e.g. a WebSocket or IPC provider.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Subscription;
+
+/// A transport which polls for new data at a fixed interval, and works with any
+/// provider, e.g. an HTTP provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Polling {
+    pub interval: Duration,
+}
+
+impl Polling {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+}
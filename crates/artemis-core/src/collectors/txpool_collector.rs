@@ -0,0 +1,153 @@
+use crate::types::{Collector, CollectorStream};
+use anyhow::Result;
+use async_trait::async_trait;
+use ethers::{
+    prelude::Middleware,
+    types::{Address, Transaction, U256},
+};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::error;
+
+/// A collector that periodically pulls the pending transactions of the node's
+/// transaction pool, and generates a stream of [events](TxpoolEvent) describing
+/// how the pool changed since the previous snapshot. The first snapshot is
+/// reported as added transactions.
+pub struct TxpoolCollector<M> {
+    provider: Arc<M>,
+    interval: Duration,
+    api: TxpoolApi,
+}
+
+/// The RPC method used by a [TxpoolCollector](TxpoolCollector) to pull the
+/// pending transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TxpoolApi {
+    /// `txpool_content`, supported by Geth and most of its forks.
+    #[default]
+    Geth,
+    /// `parity_pendingTransactions`, supported by OpenEthereum and Nethermind.
+    Parity,
+}
+
+/// A change in the transaction pool. Transactions are identified by sender and
+/// nonce.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TxpoolEvent {
+    /// A transaction entered the pool.
+    Added(Transaction),
+    /// A transaction was replaced by another one with the same sender and
+    /// nonce, e.g. to bump its gas price or to cancel it.
+    Replaced { old: Transaction, new: Transaction },
+    /// A transaction left the pool without being replaced, either because it
+    /// was included in a block or because it was dropped.
+    Evicted(Transaction),
+}
+
+/// Tracks the transactions of the pool in order to diff successive snapshots.
+#[derive(Debug, Clone, Default)]
+pub struct TxpoolTracker {
+    txs: BTreeMap<(Address, U256), Transaction>,
+}
+
+impl TxpoolTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the tracked transactions with a new snapshot of the pool, and
+    /// returns the changes ordered by sender and nonce. Additions and
+    /// replacements come before evictions.
+    pub fn apply(&mut self, mut snapshot: Vec<Transaction>) -> Vec<TxpoolEvent> {
+        snapshot.sort_by_key(|tx| (tx.from, tx.nonce));
+        let mut old = std::mem::take(&mut self.txs);
+        let mut events = vec![];
+        for tx in snapshot {
+            let key = (tx.from, tx.nonce);
+            match old.remove(&key) {
+                None => events.push(TxpoolEvent::Added(tx.clone())),
+                Some(old_tx) if old_tx.hash != tx.hash => events.push(TxpoolEvent::Replaced {
+                    old: old_tx,
+                    new: tx.clone(),
+                }),
+                Some(_) => {}
+            }
+            self.txs.insert(key, tx);
+        }
+        events.extend(old.into_values().map(TxpoolEvent::Evicted));
+        events
+    }
+}
+
+impl<M> TxpoolCollector<M> {
+    pub fn new(provider: Arc<M>, interval: Duration) -> Self {
+        Self {
+            provider,
+            interval,
+            api: TxpoolApi::default(),
+        }
+    }
+
+    pub fn with_api(mut self, api: TxpoolApi) -> Self {
+        self.api = api;
+        self
+    }
+}
+
+impl<M> TxpoolCollector<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    /// Pulls the pending transactions of the pool.
+    async fn fetch_pending(&self) -> Result<Vec<Transaction>> {
+        match self.api {
+            TxpoolApi::Geth => {
+                let content = self.provider.txpool_content().await?;
+                Ok(content
+                    .pending
+                    .into_values()
+                    .flat_map(|txs| txs.into_values())
+                    .collect())
+            }
+            TxpoolApi::Parity => Ok(self
+                .provider
+                .provider()
+                .request("parity_pendingTransactions", ())
+                .await?),
+        }
+    }
+}
+
+/// Implementation of the [Collector](Collector) trait for the [TxpoolCollector](TxpoolCollector).
+/// Snapshots which cannot be pulled are skipped, so that the next snapshot is
+/// diffed against the last one pulled.
+#[async_trait]
+impl<M> Collector<TxpoolEvent> for TxpoolCollector<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, TxpoolEvent>> {
+        let mut ticks = interval(self.interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let stream = stream::unfold(
+            (ticks, TxpoolTracker::new()),
+            move |(mut ticks, mut tracker)| async move {
+                ticks.tick().await;
+                let events = match self.fetch_pending().await {
+                    Ok(snapshot) => tracker.apply(snapshot),
+                    Err(e) => {
+                        error!("error pulling txpool snapshot: {}", e);
+                        vec![]
+                    }
+                };
+                Some((stream::iter(events), (ticks, tracker)))
+            },
+        );
+        Ok(Box::pin(stream.flatten()))
+    }
+}
@@ -1,27 +1,75 @@
-use tokio::sync::broadcast::{self, Sender};
-use tokio::task::JoinSet;
+/// This module contains the [EngineBuilder](EngineBuilder).
+mod builder;
+/// This module contains the channels connecting the engine components.
+mod channel;
+/// This module contains the Prometheus metrics of the engine, which are only
+/// recorded with the `metrics` feature.
+mod metrics;
+/// This module contains the [EngineTopology](EngineTopology) report.
+mod topology;
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use futures::FutureExt;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::{JoinError, JoinSet};
+use tokio::time::{interval_at, Interval, MissedTickBehavior};
 use tokio_stream::StreamExt;
-use tracing::{error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, info_span, warn, Instrument};
 
-use crate::types::{Collector, Executor, Strategy};
+use crate::types::{
+    ActionId, ActionOutcome, Collector, CollectorStream, ExecutionOutcome, Executor, FaultAction,
+    OutcomeReporter, Strategy, StrategyFault,
+};
+
+pub use self::builder::EngineBuilder;
+pub use self::channel::OverflowPolicy;
+use self::channel::{subscriber, Publisher, RecvError, Subscription};
+#[cfg(feature = "metrics")]
+pub use self::metrics::{serve_metrics, EngineMetrics};
+use self::metrics::{MetricsConfig, Probe};
+pub use self::topology::{CollectorInfo, EngineTopology, ExecutorInfo, StrategyInfo};
 
 /// The main engine of Artemis. This struct is responsible for orchestrating the
 /// data flow between collectors, strategies, and executors.
 pub struct Engine<E, A> {
     /// The set of collectors that the engine will use to collect events.
-    collectors: Vec<Box<dyn Collector<E>>>,
+    collectors: Vec<(Box<dyn Collector<E>>, CollectorOptions)>,
 
     /// The set of strategies that the engine will use to process events.
-    strategies: Vec<Box<dyn Strategy<E, A>>>,
+    strategies: Vec<(Box<dyn Strategy<E, A>>, StrategyOptions)>,
 
     /// The set of executors that the engine will use to execute actions.
-    executors: Vec<Box<dyn Executor<A>>>,
+    executors: Vec<(Box<dyn Executor<A>>, ExecutorOptions<A>)>,
 
-    /// The capacity of the event channel.
+    /// The capacity of the event channel of each strategy.
     event_channel_capacity: usize,
 
-    /// The capacity of the action channel.
+    /// The capacity of the action channel of each executor.
     action_channel_capacity: usize,
+
+    /// How long to wait for tasks to drain on shutdown before aborting them.
+    shutdown_timeout: Duration,
+
+    /// The restart policy used for collectors added without an explicit policy.
+    collector_restart_policy: RestartPolicy,
+
+    /// How long strategies may take to sync their state, unless overridden in
+    /// their options.
+    sync_timeout: Option<Duration>,
+
+    /// The metrics recorded by the engine, if any.
+    metrics: MetricsConfig,
 }
 
 impl<E, A> Engine<E, A> {
@@ -32,6 +80,10 @@ impl<E, A> Engine<E, A> {
             executors: vec![],
             event_channel_capacity: 512,
             action_channel_capacity: 512,
+            shutdown_timeout: Duration::from_secs(30),
+            collector_restart_policy: RestartPolicy::never(),
+            sync_timeout: None,
+            metrics: MetricsConfig::default(),
         }
     }
 
@@ -44,6 +96,110 @@ impl<E, A> Engine<E, A> {
         self.action_channel_capacity = capacity;
         self
     }
+
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    pub fn with_collector_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.collector_restart_policy = policy;
+        self
+    }
+
+    pub fn with_sync_timeout(mut self, timeout: Duration) -> Self {
+        self.sync_timeout = Some(timeout);
+        self
+    }
+
+    /// Records the pipeline metrics of the engine.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Arc<EngineMetrics>) -> Self {
+        self.metrics.metrics = Some(metrics);
+        self
+    }
+
+    /// Serves the metrics recorded by the engine on the given address while
+    /// the engine runs. The server starts once every strategy is in sync, see
+    /// [EngineHandle::metrics_addr](EngineHandle::metrics_addr).
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics_endpoint(mut self, addr: SocketAddr) -> Self {
+        self.metrics.endpoint = Some(addr);
+        self
+    }
+
+    /// Returns an [EngineBuilder](EngineBuilder) for wiring up named components.
+    pub fn builder() -> EngineBuilder<E, A> {
+        EngineBuilder::new()
+    }
+
+    /// Describes how the components of the engine are wired together: every
+    /// collector feeds every strategy, and each strategy sends its actions to
+    /// the executors it targets which accept them.
+    pub fn describe(&self) -> EngineTopology {
+        let executor_labels: Vec<String> = self
+            .executors
+            .iter()
+            .enumerate()
+            .map(|(index, (_, options))| component_label("executor", index, &options.name))
+            .collect();
+        let collectors: Vec<CollectorInfo> = self
+            .collectors
+            .iter()
+            .enumerate()
+            .map(|(index, (_, options))| CollectorInfo {
+                name: component_label("collector", index, &options.name),
+                tags: options.tags.clone(),
+            })
+            .collect();
+        let collector_names: Vec<String> = collectors.iter().map(|c| c.name.clone()).collect();
+
+        let strategies: Vec<StrategyInfo> = self
+            .strategies
+            .iter()
+            .enumerate()
+            .map(|(index, (_, options))| StrategyInfo {
+                name: component_label("strategy", index, &options.name),
+                tags: options.tags.clone(),
+                collectors: collector_names.clone(),
+                executors: if options.executors.is_empty() {
+                    executor_labels.clone()
+                } else {
+                    options.executors.clone()
+                },
+            })
+            .collect();
+
+        let executors = self
+            .executors
+            .iter()
+            .zip(&executor_labels)
+            .map(|((_, options), name)| ExecutorInfo {
+                name: name.clone(),
+                tags: options.tags.clone(),
+                filtered: options.filter.is_some(),
+                strategies: strategies
+                    .iter()
+                    .filter(|strategy| strategy.executors.contains(name))
+                    .map(|strategy| strategy.name.clone())
+                    .collect(),
+            })
+            .collect();
+
+        EngineTopology {
+            collectors,
+            strategies,
+            executors,
+        }
+    }
+}
+
+/// Returns the name of a component, which defaults to its kind and index.
+fn component_label(kind: &str, index: usize, name: &Option<String>) -> String {
+    match name {
+        Some(name) => name.clone(),
+        None => format!("{}-{}", kind, index),
+    }
 }
 
 impl<E, A> Default for Engine<E, A> {
@@ -59,84 +215,1012 @@ where
 {
     /// Adds a collector to be used by the engine.
     pub fn add_collector(&mut self, collector: Box<dyn Collector<E>>) {
-        self.collectors.push(collector);
+        self.add_collector_with_options(collector, CollectorOptions::default());
+    }
+
+    /// Adds a collector to be used by the engine, which is restarted according
+    /// to the given policy when its event stream fails or ends.
+    pub fn add_supervised_collector(
+        &mut self,
+        collector: Box<dyn Collector<E>>,
+        policy: RestartPolicy,
+    ) {
+        let options = CollectorOptions::default().with_restart_policy(policy);
+        self.add_collector_with_options(collector, options);
+    }
+
+    /// Adds a collector to be used by the engine, with the given options.
+    pub fn add_collector_with_options(
+        &mut self,
+        collector: Box<dyn Collector<E>>,
+        options: CollectorOptions,
+    ) {
+        self.collectors.push((collector, options));
     }
 
     /// Adds a strategy to be used by the engine.
     pub fn add_strategy(&mut self, strategy: Box<dyn Strategy<E, A>>) {
-        self.strategies.push(strategy);
+        self.add_strategy_with_options(strategy, StrategyOptions::default());
+    }
+
+    /// Adds a strategy to be used by the engine, with the given options.
+    pub fn add_strategy_with_options(
+        &mut self,
+        strategy: Box<dyn Strategy<E, A>>,
+        options: StrategyOptions,
+    ) {
+        self.strategies.push((strategy, options));
     }
 
     /// Adds an executor to be used by the engine.
     pub fn add_executor(&mut self, executor: Box<dyn Executor<A>>) {
-        self.executors.push(executor);
+        self.add_executor_with_options(executor, ExecutorOptions::default());
+    }
+
+    /// Adds an executor to be used by the engine, with the given options.
+    pub fn add_executor_with_options(
+        &mut self,
+        executor: Box<dyn Executor<A>>,
+        options: ExecutorOptions<A>,
+    ) {
+        self.executors.push((executor, options));
     }
 
     /// The core run loop of the engine. This function will spawn a thread for
     /// each collector, strategy, and executor. It will then orchestrate the
     /// data flow between them.
-    pub async fn run(self) -> Result<JoinSet<()>, Box<dyn std::error::Error>> {
-        let (event_sender, _): (Sender<E>, _) = broadcast::channel(self.event_channel_capacity);
-        let (action_sender, _): (Sender<A>, _) = broadcast::channel(self.action_channel_capacity);
+    ///
+    /// Strategies sync their state concurrently while the collectors start, and
+    /// the events they receive in the meantime are replayed once they are in
+    /// sync. This function returns once every strategy is in sync, or with an
+    /// error if any of them failed to sync in time.
+    ///
+    /// The returned [EngineHandle](EngineHandle) can be used to wait on the
+    /// spawned tasks, or to gracefully shut them down.
+    pub async fn run(self) -> Result<EngineHandle, Box<dyn std::error::Error>> {
+        let topology = self.describe();
+        info!("starting engine with topology:\n{}", topology);
+        if let Some(name) = topology.duplicate_executor() {
+            return Err(format!("duplicate executor name {}", name).into());
+        }
 
+        let shutdown = CancellationToken::new();
+        let stats = Arc::new(EngineStats::default());
         let mut set = JoinSet::new();
 
+        // Give every executor and strategy its own channel, so that each of them
+        // can apply its own overflow policy.
+        let mut action_senders = vec![];
+        let mut executor_names = vec![];
+        let mut action_filters = vec![];
+        let mut executors = vec![];
+        for ((executor, options), info) in self.executors.into_iter().zip(topology.executors) {
+            let (sender, receiver) =
+                subscriber(self.action_channel_capacity, options.overflow_policy);
+            let probe = self.metrics.probe(&info.name);
+            let span = info_span!("executor", name = %info.name, tags = ?info.tags);
+            action_senders.push(sender);
+            executor_names.push(info.name.clone());
+            action_filters.push(options.filter);
+            executors.push((executor, info.name, receiver, probe, span));
+        }
+        let action_publisher = Publisher::new(action_senders);
+        let action_filters = Arc::new(action_filters);
+
+        let (event_senders, strategies): (Vec<_>, Vec<_>) = self
+            .strategies
+            .into_iter()
+            .zip(topology.strategies)
+            .map(|((strategy, options), info)| {
+                let (sender, receiver) =
+                    subscriber(self.event_channel_capacity, options.overflow_policy);
+                (sender, (strategy, options, info, receiver))
+            })
+            .unzip();
+        let event_publisher = Publisher::new(event_senders);
+
         // Spawn executors in separate threads.
-        for executor in self.executors {
-            let mut receiver = action_sender.subscribe();
-            set.spawn(async move {
+        for (executor, name, mut receiver, probe, span) in executors {
+            let stats = stats.clone();
+            let task = async move {
                 info!("starting executor... ");
                 loop {
                     match receiver.recv().await {
-                        Ok(action) => match executor.execute(action).await {
-                            Ok(_) => {}
-                            Err(e) => error!("error executing action: {}", e),
-                        },
-                        Err(e) => error!("error receiving action: {}", e),
+                        Ok(Dispatched { action, outcomes }) => {
+                            probe.channel_depth(receiver.len());
+                            let outcomes = outcomes.with_executor(&name);
+                            let started = Instant::now();
+                            let execution =
+                                executor.execute_with_outcomes(action, outcomes.clone());
+                            let result = match AssertUnwindSafe(execution).catch_unwind().await {
+                                Ok(result) => result,
+                                Err(panic) => {
+                                    let reason =
+                                        format!("executor panicked: {}", panic_message(&*panic));
+                                    outcomes.report(ExecutionOutcome::Failed {
+                                        reason: reason.clone(),
+                                    });
+                                    Err(anyhow!(reason))
+                                }
+                            };
+                            probe.action_executed(started.elapsed(), result.is_ok());
+                            match result {
+                                Ok(_) => stats.record(&stats.actions_executed),
+                                Err(e) => {
+                                    stats.record(&stats.actions_failed);
+                                    error!("error executing action: {}", e)
+                                }
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            probe.dropped(skipped);
+                            stats.add(&stats.actions_dropped, skipped);
+                            warn!("executor lagged, dropped {} actions", skipped);
+                        }
+                        Err(RecvError::Overflowed(skipped)) => {
+                            probe.dropped(skipped);
+                            stats.add(&stats.actions_dropped, skipped);
+                            error!("executor fell behind by {} actions, stopping", skipped);
+                            break;
+                        }
+                        Err(RecvError::Closed) => break,
                     }
                 }
-            });
+                info!("executor stopped");
+            };
+            set.spawn(task.instrument(span));
         }
 
         // Spawn strategies in separate threads.
-        for mut strategy in self.strategies {
-            let mut event_receiver = event_sender.subscribe();
-            let action_sender = action_sender.clone();
-            strategy.sync_state().await?;
+        let mut syncs = vec![];
+        for (strategy, options, info, mut event_receiver) in strategies {
+            // Resolve the executors targeted by the strategy, which default to
+            // all of them.
+            let targets = if options.executors.is_empty() {
+                (0..executor_names.len()).collect()
+            } else {
+                let mut targets = vec![];
+                for target in &options.executors {
+                    let Some(index) = executor_names.iter().position(|name| name == target) else {
+                        return Err(format!(
+                            "strategy {} targets unknown executor {}",
+                            info.name, target
+                        )
+                        .into());
+                    };
+                    targets.push(index);
+                }
+                targets
+            };
 
-            set.spawn(async move {
-                info!("starting strategy... ");
-                loop {
-                    match event_receiver.recv().await {
-                        Ok(event) => {
-                            for action in strategy.process_event(event).await {
-                                match action_sender.send(action) {
-                                    Ok(_) => {}
-                                    Err(e) => error!("error sending action: {}", e),
+            if options.resync_interval == Some(Duration::ZERO) {
+                return Err(format!("strategy {} has a zero resync interval", info.name).into());
+            }
+
+            let span = info_span!("strategy", name = %info.name, tags = ?info.tags);
+            let (synced_sender, synced_receiver) = oneshot::channel();
+            syncs.push((info.name.clone(), synced_receiver));
+            let mut resync_interval = options.resync_interval.map(|period| {
+                let mut resync_interval = interval_at(tokio::time::Instant::now() + period, period);
+                resync_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                resync_interval
+            });
+
+            // Outcomes of the actions produced by this strategy are routed back
+            // to it through this channel.
+            let (outcome_sender, mut outcome_receiver) = mpsc::unbounded_channel();
+            let mut strategy = StrategyTask {
+                strategy,
+                router: ActionRouter {
+                    publisher: action_publisher.clone(),
+                    filters: action_filters.clone(),
+                    targets,
+                    outcomes: OutcomeReporter::new(outcome_sender),
+                    next_action_id: 0,
+                },
+                stats: stats.clone(),
+                probe: self.metrics.probe(&info.name),
+                sync_timeout: options.sync_timeout.or(self.sync_timeout),
+                panic_action: options.panic_action,
+                shutdown: shutdown.clone(),
+            };
+
+            let task = async move {
+                info!("syncing strategy... ");
+                let (synced, mut pending) = strategy.sync(&mut event_receiver, false).await;
+                let failed = synced.is_err();
+                let _ = synced_sender.send(synced);
+                if failed {
+                    return;
+                }
+
+                info!("starting strategy, replaying {} events", pending.len());
+                let mut running = match strategy.start().await {
+                    Some(fault) => {
+                        strategy
+                            .handle_fault(fault, &mut event_receiver, &mut pending)
+                            .await
+                    }
+                    None => true,
+                };
+                while running {
+                    // Events buffered while syncing are processed first.
+                    let fault = if let Some(event) = pending.pop_front() {
+                        strategy.process_event(event).await
+                    } else {
+                        tokio::select! {
+                            received = event_receiver.recv() => match received {
+                                Ok(event) => {
+                                    strategy.probe.channel_depth(event_receiver.len());
+                                    strategy.process_event(event).await
+                                }
+                                Err(RecvError::Lagged(skipped)) => {
+                                    strategy.probe.dropped(skipped);
+                                    strategy.stats.add(&strategy.stats.events_dropped, skipped);
+                                    warn!("strategy lagged, dropped {} events", skipped);
+                                    None
+                                }
+                                Err(RecvError::Overflowed(skipped)) => {
+                                    strategy.probe.dropped(skipped);
+                                    strategy.stats.add(&strategy.stats.events_dropped, skipped);
+                                    error!("strategy fell behind by {} events, stopping", skipped);
+                                    break;
                                 }
+                                Err(RecvError::Closed) => break,
+                            },
+                            Some(outcome) = outcome_receiver.recv() => {
+                                strategy.process_outcome(outcome).await
+                            }
+                            _ = next_tick(&mut resync_interval) => {
+                                info!("resyncing strategy on interval");
+                                running = strategy.resync(&mut event_receiver, &mut pending).await;
+                                None
                             }
                         }
-                        Err(e) => error!("error receiving event: {}", e),
+                    };
+
+                    if let Some(fault) = fault {
+                        running = strategy
+                            .handle_fault(fault, &mut event_receiver, &mut pending)
+                            .await;
                     }
                 }
-            });
+                strategy.stop().await;
+                info!("strategy stopped");
+            };
+            set.spawn(task.instrument(span));
         }
 
         // Spawn collectors in separate threads.
-        for collector in self.collectors {
-            let event_sender = event_sender.clone();
-            set.spawn(async move {
+        for ((collector, options), info) in self.collectors.into_iter().zip(topology.collectors) {
+            let event_publisher = event_publisher.clone();
+            let shutdown = shutdown.clone();
+            let stats = stats.clone();
+            let probe = self.metrics.probe(&info.name);
+            let span = info_span!("collector", name = %info.name, tags = ?info.tags);
+            let policy = options
+                .restart_policy
+                .unwrap_or_else(|| self.collector_restart_policy.clone());
+            let task = async move {
                 info!("starting collector... ");
-                let mut event_stream = collector.get_event_stream().await.unwrap();
-                while let Some(event) = event_stream.next().await {
-                    match event_sender.send(event) {
-                        Ok(_) => {}
-                        Err(e) => error!("error sending event: {}", e),
+                let mut restarts = 0;
+                loop {
+                    let event_stream = tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        event_stream = collector.get_event_stream() => event_stream,
+                    };
+                    match event_stream {
+                        Ok(event_stream) => {
+                            let forwarded = forward_events(
+                                event_stream,
+                                &event_publisher,
+                                &shutdown,
+                                &stats,
+                                &probe,
+                            )
+                            .await;
+                            if shutdown.is_cancelled() {
+                                break;
+                            }
+                            // A stream that produced events was healthy, so start
+                            // backing off from scratch.
+                            if forwarded > 0 {
+                                restarts = 0;
+                            }
+                            warn!("collector event stream ended");
+                        }
+                        Err(e) => error!("error getting collector event stream: {}", e),
+                    }
+
+                    let Some(backoff) = policy.backoff(restarts) else {
+                        error!("collector stopped after {} restarts", restarts);
+                        break;
+                    };
+                    restarts += 1;
+                    stats.record(&stats.collector_restarts);
+                    warn!(
+                        "restarting collector in {:?} (attempt {})",
+                        backoff, restarts
+                    );
+                    tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        _ = tokio::time::sleep(backoff) => {}
                     }
                 }
-            });
+                info!("collector stopped");
+            };
+            set.spawn(task.instrument(span));
+        }
+
+        // Wait for every strategy to be in sync. Returning early drops the
+        // join set, which aborts the tasks spawned so far.
+        for (name, synced) in syncs {
+            match synced.await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => return Err(format!("strategy {} failed to sync: {}", name, e).into()),
+                Err(_) => return Err(format!("strategy {} stopped while syncing", name).into()),
+            }
         }
 
-        Ok(set)
+        // Only serve the metrics once the engine started, since nothing stops
+        // the endpoint if this function returns an error.
+        let metrics_addr = self.metrics.serve(&shutdown)?;
+
+        Ok(EngineHandle {
+            set,
+            shutdown,
+            shutdown_timeout: self.shutdown_timeout,
+            stats,
+            metrics_addr,
+        })
+    }
+}
+
+/// Forwards events from a collector stream to the strategies until the stream
+/// ends or a shutdown is requested, returning the number of events forwarded.
+async fn forward_events<E: Clone>(
+    mut event_stream: CollectorStream<'_, E>,
+    event_publisher: &Publisher<E>,
+    shutdown: &CancellationToken,
+    stats: &EngineStats,
+    probe: &Probe,
+) -> u64 {
+    let mut forwarded = 0;
+    loop {
+        let event = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            event = event_stream.next() => match event {
+                Some(event) => event,
+                None => break,
+            },
+        };
+        forwarded += 1;
+        stats.record(&stats.events_collected);
+        probe.event_collected();
+        event_publisher.send(event).await;
+    }
+    forwarded
+}
+
+/// A strategy along with everything its task needs to process events.
+struct StrategyTask<E, A> {
+    strategy: Box<dyn Strategy<E, A>>,
+    router: ActionRouter<A>,
+    stats: Arc<EngineStats>,
+    probe: Probe,
+    sync_timeout: Option<Duration>,
+    panic_action: FaultAction,
+    shutdown: CancellationToken,
+}
+
+impl<E: Clone, A: Clone + fmt::Debug> StrategyTask<E, A> {
+    /// Syncs the state of the strategy, or resyncs it, buffering the events it
+    /// receives in the meantime so that they can be replayed once it is in sync.
+    async fn sync(
+        &mut self,
+        event_receiver: &mut Subscription<E>,
+        resync: bool,
+    ) -> (anyhow::Result<()>, VecDeque<E>) {
+        let sync_timeout = self.sync_timeout;
+        let strategy = &mut self.strategy;
+        let sync = async move {
+            let sync = if resync {
+                strategy.resync()
+            } else {
+                strategy.sync_state()
+            };
+            let sync = AssertUnwindSafe(sync).catch_unwind();
+            let synced = match sync_timeout {
+                Some(sync_timeout) => match tokio::time::timeout(sync_timeout, sync).await {
+                    Ok(synced) => synced,
+                    Err(_) => return Err(anyhow!("timed out after {:?}", sync_timeout)),
+                },
+                None => sync.await,
+            };
+            synced.unwrap_or_else(|panic| Err(anyhow!("panicked: {}", panic_message(&*panic))))
+        };
+        tokio::pin!(sync);
+
+        let mut buffered = VecDeque::new();
+        let mut open = true;
+        let synced = loop {
+            tokio::select! {
+                synced = &mut sync => break synced,
+                received = event_receiver.recv(), if open => match received {
+                    Ok(event) => buffered.push_back(event),
+                    Err(RecvError::Lagged(skipped) | RecvError::Overflowed(skipped)) => {
+                        self.probe.dropped(skipped);
+                        self.stats.add(&self.stats.events_dropped, skipped);
+                        warn!("strategy lagged while syncing, dropped {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => open = false,
+                },
+            }
+        };
+        (synced, buffered)
+    }
+
+    /// Processes an event and dispatches the resulting actions, returning the
+    /// fault reported by the strategy, if any.
+    async fn process_event(&mut self, event: E) -> Option<StrategyFault> {
+        self.stats.record(&self.stats.events_processed);
+        let started = Instant::now();
+        let actions = match self.guard(|strategy| strategy.process_event(event)).await {
+            Ok(actions) => actions,
+            Err(fault) => return Some(fault),
+        };
+        self.probe.event_processed(started.elapsed(), actions.len());
+        self.dispatch(actions).await
+    }
+
+    /// Processes the outcome of an action and dispatches the resulting
+    /// actions, returning the fault reported by the strategy, if any.
+    async fn process_outcome(&mut self, outcome: ActionOutcome) -> Option<StrategyFault> {
+        let actions = match self
+            .guard(|strategy| strategy.on_execution_outcome(outcome))
+            .await
+        {
+            Ok(actions) => actions,
+            Err(fault) => return Some(fault),
+        };
+        self.dispatch(actions).await
+    }
+
+    /// Starts the strategy and dispatches its startup actions, returning the
+    /// fault reported by the strategy, if any.
+    async fn start(&mut self) -> Option<StrategyFault> {
+        let actions = match self.guard(|strategy| strategy.on_start()).await {
+            Ok(actions) => actions,
+            Err(fault) => return Some(fault),
+        };
+        self.dispatch(actions).await
+    }
+
+    /// Stops the strategy and dispatches its shutdown actions, which are still
+    /// executed since the executors only stop once every strategy has.
+    async fn stop(&mut self) {
+        info!("stopping strategy...");
+        let fault = match self.guard(|strategy| strategy.on_shutdown()).await {
+            Ok(actions) => self.dispatch(actions).await,
+            Err(fault) => Some(fault),
+        };
+        if let Some(fault) = fault {
+            self.stats.record(&self.stats.strategy_faults);
+            error!("strategy failed to shut down: {}", fault.reason);
+        }
+    }
+
+    /// Resyncs the strategy, queueing the events it receives in the meantime.
+    /// Returns whether the strategy is still running.
+    async fn resync(
+        &mut self,
+        event_receiver: &mut Subscription<E>,
+        pending: &mut VecDeque<E>,
+    ) -> bool {
+        let (synced, buffered) = self.sync(event_receiver, true).await;
+        pending.extend(buffered);
+        if let Err(e) = synced {
+            error!("disabling strategy, failed to resync: {}", e);
+            return false;
+        }
+        true
+    }
+
+    /// Handles a fault reported by the strategy according to its action.
+    /// Returns whether the strategy is still running.
+    async fn handle_fault(
+        &mut self,
+        fault: StrategyFault,
+        event_receiver: &mut Subscription<E>,
+        pending: &mut VecDeque<E>,
+    ) -> bool {
+        self.stats.record(&self.stats.strategy_faults);
+        match fault.action {
+            FaultAction::Disable => {
+                error!("disabling strategy: {}", fault.reason);
+                false
+            }
+            FaultAction::Stop => {
+                error!("stopping engine: {}", fault.reason);
+                self.shutdown.cancel();
+                false
+            }
+            FaultAction::Resync => {
+                warn!("resyncing strategy: {}", fault.reason);
+                self.resync(event_receiver, pending).await
+            }
+        }
+    }
+
+    async fn dispatch(&mut self, actions: Vec<A>) -> Option<StrategyFault> {
+        for action in actions {
+            self.router.dispatch(action, &self.stats).await;
+        }
+        self.strategy.take_fault()
+    }
+
+    /// Calls the strategy behind a fault boundary, turning a panic into a
+    /// fault handled according to the strategy's panic action.
+    async fn guard<'a, T, F>(
+        &'a mut self,
+        call: impl FnOnce(&'a mut Box<dyn Strategy<E, A>>) -> F,
+    ) -> Result<T, StrategyFault>
+    where
+        F: Future<Output = T> + 'a,
+    {
+        let panic_action = self.panic_action;
+        AssertUnwindSafe(call(&mut self.strategy))
+            .catch_unwind()
+            .await
+            .map_err(|panic| {
+                StrategyFault::new(
+                    panic_action,
+                    format!("strategy panicked: {}", panic_message(&*panic)),
+                )
+            })
+    }
+}
+
+/// Waits for the next tick of an interval, or forever if there is none.
+async fn next_tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Returns the message of a panic payload, if it is a string.
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// An action on its way to the executors, along with the reporter for the
+/// outcomes of executing it.
+#[derive(Clone)]
+struct Dispatched<A> {
+    action: A,
+    outcomes: OutcomeReporter,
+}
+
+/// Delivers the actions of a strategy to the executors which accept them.
+struct ActionRouter<A> {
+    publisher: Publisher<Dispatched<A>>,
+    /// The filter of every executor, by executor index.
+    filters: Arc<Vec<Option<ActionFilter<A>>>>,
+    /// The indices of the executors targeted by the strategy.
+    targets: Vec<usize>,
+    outcomes: OutcomeReporter,
+    /// The id of the next action of the strategy, which is assigned whether or
+    /// not the action is routed so that strategies can count their actions.
+    next_action_id: ActionId,
+}
+
+impl<A: Clone + fmt::Debug> ActionRouter<A> {
+    async fn dispatch(&mut self, action: A, stats: &EngineStats) {
+        let action_id = self.next_action_id;
+        self.next_action_id += 1;
+        stats.record(&stats.actions_emitted);
+        let recipients: Vec<usize> = self
+            .targets
+            .iter()
+            .copied()
+            .filter(|&index| match &self.filters[index] {
+                Some(filter) => filter(&action),
+                None => true,
+            })
+            .collect();
+        if recipients.is_empty() {
+            stats.record(&stats.actions_unrouted);
+            warn!("no executor accepts action {:?}", action);
+            return;
+        }
+        let dispatched = Dispatched {
+            action,
+            outcomes: self.outcomes.with_action_id(action_id),
+        };
+        self.publisher.send_to(&recipients, dispatched).await;
+    }
+}
+
+/// Options for a collector added to the engine.
+#[derive(Debug, Clone, Default)]
+pub struct CollectorOptions {
+    /// The name of the collector in logs, metrics and errors.
+    pub name: Option<String>,
+    /// Free-form tags describing the collector.
+    pub tags: Vec<String>,
+    /// The policy used to restart the collector, overriding the engine's
+    /// [restart policy](Engine::with_collector_restart_policy).
+    pub restart_policy: Option<RestartPolicy>,
+}
+
+impl CollectorOptions {
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = Some(policy);
+        self
+    }
+}
+
+/// Options for a strategy added to the engine.
+#[derive(Debug, Clone, Default)]
+pub struct StrategyOptions {
+    /// The name of the strategy in logs, metrics and errors.
+    pub name: Option<String>,
+    /// Free-form tags describing the strategy.
+    pub tags: Vec<String>,
+    /// What to do when the strategy falls behind on its events.
+    pub overflow_policy: OverflowPolicy,
+    /// The names of the executors that the strategy's actions are sent to. If
+    /// empty, actions are sent to every executor.
+    pub executors: Vec<String>,
+    /// How long the strategy may take to sync its state, overriding the
+    /// engine's [sync timeout](Engine::with_sync_timeout).
+    pub sync_timeout: Option<Duration>,
+    /// How to handle the strategy panicking while processing an event or an
+    /// execution outcome.
+    pub panic_action: FaultAction,
+    /// How often to [resync](Strategy::resync) the strategy, if at all.
+    pub resync_interval: Option<Duration>,
+}
+
+impl StrategyOptions {
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sends the strategy's actions to the executor with the given
+    /// [name](ExecutorOptions::with_name), or with the `executor-N` label of
+    /// the N-th executor if it is unnamed, as in [Engine::describe](Engine::describe).
+    /// Can be called several times to target several executors.
+    pub fn with_executor(mut self, name: impl Into<String>) -> Self {
+        self.executors.push(name.into());
+        self
+    }
+
+    pub fn with_sync_timeout(mut self, timeout: Duration) -> Self {
+        self.sync_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_panic_action(mut self, action: FaultAction) -> Self {
+        self.panic_action = action;
+        self
+    }
+
+    pub fn with_resync_interval(mut self, interval: Duration) -> Self {
+        self.resync_interval = Some(interval);
+        self
+    }
+}
+
+/// A predicate deciding whether an executor accepts an action.
+pub type ActionFilter<A> = Arc<dyn Fn(&A) -> bool + Send + Sync>;
+
+/// Options for an executor added to the engine.
+pub struct ExecutorOptions<A> {
+    /// What to do when the executor falls behind on its actions.
+    pub overflow_policy: OverflowPolicy,
+    /// The name of the executor in logs, metrics and errors, which strategies
+    /// also use to target it.
+    pub name: Option<String>,
+    /// Free-form tags describing the executor.
+    pub tags: Vec<String>,
+    /// Only actions matching this filter are delivered to the executor. If
+    /// `None`, every action is delivered.
+    pub filter: Option<ActionFilter<A>>,
+}
+
+impl<A> ExecutorOptions<A> {
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Only delivers the actions matching the filter to the executor, e.g.
+    /// `|action| matches!(action, Actions::SubmitTxToMempool(_))`.
+    pub fn with_filter(mut self, filter: impl Fn(&A) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+}
+
+impl<A> Default for ExecutorOptions<A> {
+    fn default() -> Self {
+        Self {
+            overflow_policy: OverflowPolicy::default(),
+            name: None,
+            tags: vec![],
+            filter: None,
+        }
+    }
+}
+
+impl<A> Clone for ExecutorOptions<A> {
+    fn clone(&self) -> Self {
+        Self {
+            overflow_policy: self.overflow_policy,
+            name: self.name.clone(),
+            tags: self.tags.clone(),
+            filter: self.filter.clone(),
+        }
+    }
+}
+
+impl<A> fmt::Debug for ExecutorOptions<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutorOptions")
+            .field("overflow_policy", &self.overflow_policy)
+            .field("name", &self.name)
+            .field("tags", &self.tags)
+            .field("filter", &self.filter.is_some())
+            .finish()
+    }
+}
+
+/// The policy used by the engine to restart a collector whose event stream
+/// failed to start or ended, e.g. because a websocket connection dropped.
+///
+/// Restarts are delayed with an exponential backoff, which is reset once a
+/// restarted stream produces events again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Maximum number of consecutive restarts, or `None` to restart forever.
+    pub max_retries: Option<u32>,
+    /// Delay before the first restart.
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between restarts.
+    pub max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    /// Never restart the collector.
+    pub fn never() -> Self {
+        Self {
+            max_retries: Some(0),
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Restart the collector forever, doubling the delay between consecutive
+    /// restarts from `initial_backoff` up to `max_backoff`.
+    pub fn exponential(initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_retries: None,
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    /// Caps the number of consecutive restarts.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Returns the delay before the next restart, given the number of
+    /// consecutive restarts so far, or `None` if the collector should not be
+    /// restarted again.
+    pub fn backoff(&self, restarts: u32) -> Option<Duration> {
+        if matches!(self.max_retries, Some(max) if restarts >= max) {
+            return None;
+        }
+        let factor = 2u32.saturating_pow(restarts);
+        Some(
+            self.initial_backoff
+                .saturating_mul(factor)
+                .min(self.max_backoff),
+        )
+    }
+}
+
+/// A handle to the tasks spawned by [Engine::run](Engine::run).
+///
+/// Shutting down the engine stops the collectors first. Once every collector
+/// has exited the event channel closes, so strategies drain the events still in
+/// flight and exit, which in turn closes the action channel and lets executors
+/// finish the pending actions.
+pub struct EngineHandle {
+    set: JoinSet<()>,
+    shutdown: CancellationToken,
+    shutdown_timeout: Duration,
+    stats: Arc<EngineStats>,
+    metrics_addr: Option<SocketAddr>,
+}
+
+impl EngineHandle {
+    /// Returns a token which starts a graceful shutdown when cancelled, e.g. from
+    /// a signal handler.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Returns the address the metrics are served on, if an endpoint is
+    /// configured. This is where to find the port picked by the OS for an
+    /// endpoint on port 0.
+    pub fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.metrics_addr
+    }
+
+    /// Waits for the next engine task to exit.
+    pub async fn join_next(&mut self) -> Option<Result<(), JoinError>> {
+        self.set.join_next().await
+    }
+
+    /// Gracefully shuts down the engine, returning a summary once every task
+    /// has exited.
+    pub async fn shutdown(self) -> EngineSummary {
+        self.shutdown.cancel();
+        self.wait().await
+    }
+
+    /// Waits until every engine task has exited, either because all collectors
+    /// ended or because a shutdown was requested through the
+    /// [shutdown token](EngineHandle::shutdown_token). Tasks that are still running
+    /// once the shutdown timeout has elapsed are aborted.
+    pub async fn wait(mut self) -> EngineSummary {
+        let deadline = tokio::time::sleep(Duration::MAX);
+        tokio::pin!(deadline);
+        let mut draining = false;
+        let mut tasks_failed = 0;
+
+        loop {
+            tokio::select! {
+                res = self.set.join_next() => match res {
+                    Some(Ok(())) => {}
+                    Some(Err(e)) => {
+                        error!("engine task failed: {}", e);
+                        tasks_failed += 1;
+                    }
+                    None => break,
+                },
+                _ = self.shutdown.cancelled(), if !draining => {
+                    info!("shutting down engine...");
+                    draining = true;
+                    deadline
+                        .as_mut()
+                        .reset(tokio::time::Instant::now() + self.shutdown_timeout);
+                }
+                _ = &mut deadline, if draining => {
+                    warn!("shutdown timed out, aborting {} tasks", self.set.len());
+                    tasks_failed += self.set.len() as u64;
+                    self.set.shutdown().await;
+                    break;
+                }
+            }
+        }
+
+        // Stop anything still tied to the engine, such as the metrics endpoint.
+        self.shutdown.cancel();
+        let summary = self.stats.summary(tasks_failed);
+        info!("engine stopped: {:?}", summary);
+        summary
+    }
+}
+
+/// A summary of an engine run, returned once all of its tasks have exited.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EngineSummary {
+    /// Number of events emitted by collectors.
+    pub events_collected: u64,
+    /// Number of events delivered to strategies.
+    pub events_processed: u64,
+    /// Number of events dropped because a strategy fell behind.
+    pub events_dropped: u64,
+    /// Number of actions emitted by strategies.
+    pub actions_emitted: u64,
+    /// Number of actions which no executor accepted.
+    pub actions_unrouted: u64,
+    /// Number of actions dropped because an executor fell behind.
+    pub actions_dropped: u64,
+    /// Number of action executions that succeeded.
+    pub actions_executed: u64,
+    /// Number of action executions that failed.
+    pub actions_failed: u64,
+    /// Number of times a collector was restarted.
+    pub collector_restarts: u64,
+    /// Number of faults reported by strategies, including panics.
+    pub strategy_faults: u64,
+    /// Number of tasks that panicked or had to be aborted.
+    pub tasks_failed: u64,
+}
+
+/// Counters shared between the engine tasks.
+#[derive(Debug, Default)]
+struct EngineStats {
+    events_collected: AtomicU64,
+    events_processed: AtomicU64,
+    events_dropped: AtomicU64,
+    actions_emitted: AtomicU64,
+    actions_unrouted: AtomicU64,
+    actions_dropped: AtomicU64,
+    actions_executed: AtomicU64,
+    actions_failed: AtomicU64,
+    collector_restarts: AtomicU64,
+    strategy_faults: AtomicU64,
+}
+
+impl EngineStats {
+    fn record(&self, counter: &AtomicU64) {
+        self.add(counter, 1);
+    }
+
+    fn add(&self, counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn summary(&self, tasks_failed: u64) -> EngineSummary {
+        EngineSummary {
+            events_collected: self.events_collected.load(Ordering::Relaxed),
+            events_processed: self.events_processed.load(Ordering::Relaxed),
+            events_dropped: self.events_dropped.load(Ordering::Relaxed),
+            actions_emitted: self.actions_emitted.load(Ordering::Relaxed),
+            actions_unrouted: self.actions_unrouted.load(Ordering::Relaxed),
+            actions_dropped: self.actions_dropped.load(Ordering::Relaxed),
+            actions_executed: self.actions_executed.load(Ordering::Relaxed),
+            actions_failed: self.actions_failed.load(Ordering::Relaxed),
+            collector_restarts: self.collector_restarts.load(Ordering::Relaxed),
+            strategy_faults: self.strategy_faults.load(Ordering::Relaxed),
+            tasks_failed,
+        }
     }
 }
@@ -0,0 +1,138 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::types::{Collector, Executor, Strategy};
+
+use super::{CollectorOptions, Engine, ExecutorOptions, RestartPolicy, StrategyOptions};
+
+/// Builds an [Engine](Engine) whose components all have a name, which is used
+/// in tracing spans, metrics, errors and the [topology](Engine::describe)
+/// report.
+///
+/// ```ignore
+/// let engine = Engine::builder()
+///     .collector("blocks", Box::new(block_collector))
+///     .strategy("arb", Box::new(strategy))
+///     .executor_with_options(
+///         "mempool",
+///         Box::new(executor),
+///         ExecutorOptions::default().with_tag("mainnet"),
+///     )
+///     .build();
+/// ```
+pub struct EngineBuilder<E, A> {
+    engine: Engine<E, A>,
+}
+
+impl<E, A> EngineBuilder<E, A> {
+    pub fn new() -> Self {
+        Self {
+            engine: Engine::new(),
+        }
+    }
+
+    pub fn with_event_channel_capacity(mut self, capacity: usize) -> Self {
+        self.engine = self.engine.with_event_channel_capacity(capacity);
+        self
+    }
+
+    pub fn with_action_channel_capacity(mut self, capacity: usize) -> Self {
+        self.engine = self.engine.with_action_channel_capacity(capacity);
+        self
+    }
+
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.engine = self.engine.with_shutdown_timeout(timeout);
+        self
+    }
+
+    pub fn with_collector_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.engine = self.engine.with_collector_restart_policy(policy);
+        self
+    }
+
+    pub fn with_sync_timeout(mut self, timeout: Duration) -> Self {
+        self.engine = self.engine.with_sync_timeout(timeout);
+        self
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: std::sync::Arc<super::EngineMetrics>) -> Self {
+        self.engine = self.engine.with_metrics(metrics);
+        self
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics_endpoint(mut self, addr: std::net::SocketAddr) -> Self {
+        self.engine = self.engine.with_metrics_endpoint(addr);
+        self
+    }
+
+    /// Returns the engine with every component added so far.
+    pub fn build(self) -> Engine<E, A> {
+        self.engine
+    }
+}
+
+impl<E, A> Default for EngineBuilder<E, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, A> EngineBuilder<E, A>
+where
+    E: Send + Clone + 'static + Debug,
+    A: Send + Clone + 'static + Debug,
+{
+    /// Adds a named collector.
+    pub fn collector(self, name: impl Into<String>, collector: Box<dyn Collector<E>>) -> Self {
+        self.collector_with_options(name, collector, CollectorOptions::default())
+    }
+
+    /// Adds a named collector with the given options, e.g. tags.
+    pub fn collector_with_options(
+        mut self,
+        name: impl Into<String>,
+        collector: Box<dyn Collector<E>>,
+        options: CollectorOptions,
+    ) -> Self {
+        self.engine
+            .add_collector_with_options(collector, options.with_name(name));
+        self
+    }
+
+    /// Adds a named strategy.
+    pub fn strategy(self, name: impl Into<String>, strategy: Box<dyn Strategy<E, A>>) -> Self {
+        self.strategy_with_options(name, strategy, StrategyOptions::default())
+    }
+
+    /// Adds a named strategy with the given options, e.g. tags.
+    pub fn strategy_with_options(
+        mut self,
+        name: impl Into<String>,
+        strategy: Box<dyn Strategy<E, A>>,
+        options: StrategyOptions,
+    ) -> Self {
+        self.engine
+            .add_strategy_with_options(strategy, options.with_name(name));
+        self
+    }
+
+    /// Adds a named executor.
+    pub fn executor(self, name: impl Into<String>, executor: Box<dyn Executor<A>>) -> Self {
+        self.executor_with_options(name, executor, ExecutorOptions::default())
+    }
+
+    /// Adds a named executor with the given options, e.g. tags.
+    pub fn executor_with_options(
+        mut self,
+        name: impl Into<String>,
+        executor: Box<dyn Executor<A>>,
+        options: ExecutorOptions<A>,
+    ) -> Self {
+        self.engine
+            .add_executor_with_options(executor, options.with_name(name));
+        self
+    }
+}
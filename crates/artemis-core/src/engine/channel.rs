@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use tokio::sync::{broadcast, mpsc};
+
+/// The policy applied when a subscriber falls behind, i.e. when more items are
+/// queued for it than the capacity of its channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued items so that the subscriber skips ahead, and
+    /// record how many were dropped.
+    #[default]
+    DropOldest,
+    /// Make the sender wait until the subscriber has caught up.
+    Block,
+    /// Stop the subscriber.
+    Stop,
+}
+
+/// Creates the channel for a single subscriber with the given capacity and
+/// overflow policy.
+pub(crate) fn subscriber<T: Clone>(
+    capacity: usize,
+    policy: OverflowPolicy,
+) -> (SubscriberSender<T>, Subscription<T>) {
+    match policy {
+        OverflowPolicy::Block => {
+            let (sender, receiver) = mpsc::channel(capacity);
+            (
+                SubscriberSender::Blocking(sender),
+                Subscription::Blocking(receiver),
+            )
+        }
+        OverflowPolicy::DropOldest | OverflowPolicy::Stop => {
+            let (sender, receiver) = broadcast::channel(capacity);
+            (
+                SubscriberSender::Queue(sender),
+                Subscription::Queue { receiver, policy },
+            )
+        }
+    }
+}
+
+/// Publishes items to a fixed set of subscribers, each of which has its own
+/// bounded channel so that a slow subscriber does not make the others lag.
+pub(crate) struct Publisher<T> {
+    subscribers: Arc<Vec<SubscriberSender<T>>>,
+}
+
+impl<T> Clone for Publisher<T> {
+    fn clone(&self) -> Self {
+        Self {
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Publisher<T> {
+    pub(crate) fn new(subscribers: Vec<SubscriberSender<T>>) -> Self {
+        Self {
+            subscribers: Arc::new(subscribers),
+        }
+    }
+
+    /// Sends an item to every subscriber, waiting for the subscribers which use
+    /// [OverflowPolicy::Block] to make room for it.
+    pub(crate) async fn send(&self, item: T) {
+        for subscriber in self.subscribers.iter() {
+            subscriber.send(item.clone()).await;
+        }
+    }
+
+    /// Sends an item to the subscribers with the given indices only, cloning it
+    /// once per recipient but the last.
+    pub(crate) async fn send_to(&self, indices: &[usize], item: T) {
+        let Some((&last, rest)) = indices.split_last() else {
+            return;
+        };
+        for &index in rest {
+            self.subscribers[index].send(item.clone()).await;
+        }
+        self.subscribers[last].send(item).await;
+    }
+}
+
+/// The sending half of a subscriber channel.
+pub(crate) enum SubscriberSender<T> {
+    Queue(broadcast::Sender<T>),
+    Blocking(mpsc::Sender<T>),
+}
+
+impl<T> SubscriberSender<T> {
+    async fn send(&self, item: T) {
+        // Sending only fails once the subscriber has stopped, in which case
+        // there is no one left to deliver the item to.
+        match self {
+            Self::Queue(sender) => {
+                let _ = sender.send(item);
+            }
+            Self::Blocking(sender) => {
+                let _ = sender.send(item).await;
+            }
+        }
+    }
+}
+
+/// The receiving half of a subscriber channel.
+pub(crate) enum Subscription<T> {
+    Queue {
+        receiver: broadcast::Receiver<T>,
+        policy: OverflowPolicy,
+    },
+    Blocking(mpsc::Receiver<T>),
+}
+
+/// The error returned when a [Subscription](Subscription) has no item to
+/// return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RecvError {
+    /// Every publisher has been dropped and all queued items were received.
+    Closed,
+    /// The subscriber fell behind and skipped this many items.
+    Lagged(u64),
+    /// The subscriber fell behind by this many items and has to stop.
+    Overflowed(u64),
+}
+
+impl<T: Clone> Subscription<T> {
+    /// Returns the number of items queued for the subscriber.
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Queue { receiver, .. } => receiver.len(),
+            Self::Blocking(receiver) => receiver.len(),
+        }
+    }
+
+    /// Receives the next item.
+    pub(crate) async fn recv(&mut self) -> Result<T, RecvError> {
+        match self {
+            Self::Queue { receiver, policy } => match receiver.recv().await {
+                Ok(item) => Ok(item),
+                Err(broadcast::error::RecvError::Closed) => Err(RecvError::Closed),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => match policy {
+                    OverflowPolicy::Stop => Err(RecvError::Overflowed(skipped)),
+                    _ => Err(RecvError::Lagged(skipped)),
+                },
+            },
+            Self::Blocking(receiver) => receiver.recv().await.ok_or(RecvError::Closed),
+        }
+    }
+}
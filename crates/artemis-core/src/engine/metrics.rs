@@ -0,0 +1,305 @@
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use std::{
+    convert::Infallible,
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+};
+
+#[cfg(feature = "metrics")]
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+#[cfg(feature = "metrics")]
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+#[cfg(feature = "metrics")]
+use tokio_util::sync::CancellationToken;
+#[cfg(feature = "metrics")]
+use tracing::{error, info};
+
+/// Prometheus metrics for the engine pipeline. Every metric is labelled with the
+/// component it describes, e.g. `collector-0` or the name of an executor.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone)]
+pub struct EngineMetrics {
+    registry: Registry,
+    /// Events emitted by each collector.
+    collector_events: IntCounterVec,
+    /// Time taken by each strategy to process an event.
+    strategy_latency: HistogramVec,
+    /// Actions emitted by each strategy.
+    strategy_actions: IntCounterVec,
+    /// Action executions by executor and result.
+    executor_results: IntCounterVec,
+    /// Time taken by each executor to execute an action.
+    executor_latency: HistogramVec,
+    /// Items queued in the channel of each strategy and executor.
+    channel_depth: IntGaugeVec,
+    /// Items dropped because a strategy or executor fell behind.
+    channel_dropped: IntCounterVec,
+}
+
+#[cfg(feature = "metrics")]
+impl EngineMetrics {
+    /// Creates the engine metrics in a new registry.
+    pub fn new() -> prometheus::Result<Self> {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Creates the engine metrics in the given registry, e.g. to serve them
+    /// along with the metrics of a strategy.
+    pub fn with_registry(registry: Registry) -> prometheus::Result<Self> {
+        let latency_buckets = vec![
+            0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0,
+        ];
+        let collector_events = IntCounterVec::new(
+            Opts::new(
+                "artemis_collector_events_total",
+                "Total number of events emitted by each collector",
+            ),
+            &["component"],
+        )?;
+        let strategy_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "artemis_strategy_processing_seconds",
+                "Time taken by each strategy to process an event in seconds",
+            )
+            .buckets(latency_buckets.clone()),
+            &["component"],
+        )?;
+        let strategy_actions = IntCounterVec::new(
+            Opts::new(
+                "artemis_strategy_actions_total",
+                "Total number of actions emitted by each strategy",
+            ),
+            &["component"],
+        )?;
+        let executor_results = IntCounterVec::new(
+            Opts::new(
+                "artemis_executor_actions_total",
+                "Total number of actions executed by each executor, by result",
+            ),
+            &["component", "result"],
+        )?;
+        let executor_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "artemis_executor_latency_seconds",
+                "Time taken by each executor to execute an action in seconds",
+            )
+            .buckets(latency_buckets),
+            &["component"],
+        )?;
+        let channel_depth = IntGaugeVec::new(
+            Opts::new(
+                "artemis_channel_depth",
+                "Number of items queued for each strategy and executor",
+            ),
+            &["component"],
+        )?;
+        let channel_dropped = IntCounterVec::new(
+            Opts::new(
+                "artemis_channel_dropped_total",
+                "Total number of items dropped because a strategy or executor lagged",
+            ),
+            &["component"],
+        )?;
+
+        registry.register(Box::new(collector_events.clone()))?;
+        registry.register(Box::new(strategy_latency.clone()))?;
+        registry.register(Box::new(strategy_actions.clone()))?;
+        registry.register(Box::new(executor_results.clone()))?;
+        registry.register(Box::new(executor_latency.clone()))?;
+        registry.register(Box::new(channel_depth.clone()))?;
+        registry.register(Box::new(channel_dropped.clone()))?;
+
+        Ok(Self {
+            registry,
+            collector_events,
+            strategy_latency,
+            strategy_actions,
+            executor_results,
+            executor_latency,
+            channel_depth,
+            channel_dropped,
+        })
+    }
+
+    /// Returns the registry containing the engine metrics.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+}
+
+/// Serves the metrics in the registry in the Prometheus text format on the
+/// given address, until the shutdown token is cancelled.
+#[cfg(feature = "metrics")]
+pub async fn serve_metrics(
+    registry: Registry,
+    addr: SocketAddr,
+    shutdown: CancellationToken,
+) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    serve_listener(registry, listener, shutdown).await
+}
+
+/// Serves the metrics in the registry on a listener which is already bound.
+#[cfg(feature = "metrics")]
+async fn serve_listener(
+    registry: Registry,
+    listener: TcpListener,
+    shutdown: CancellationToken,
+) -> anyhow::Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let registry = registry.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |_: Request<Body>| {
+                let registry = registry.clone();
+                async move { Ok::<_, Infallible>(encode(&registry)) }
+            }))
+        }
+    });
+
+    listener.set_nonblocking(true)?;
+    let server = Server::from_tcp(listener)?.serve(make_service);
+    info!("serving metrics on {}", server.local_addr());
+    server
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await?;
+    Ok(())
+}
+
+#[cfg(feature = "metrics")]
+fn encode(registry: &Registry) -> Response<Body> {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    if let Err(e) = encoder.encode(&registry.gather(), &mut buffer) {
+        error!("error encoding metrics: {}", e);
+    }
+    Response::builder()
+        .header(CONTENT_TYPE, encoder.format_type())
+        .body(Body::from(buffer))
+        .unwrap_or_default()
+}
+
+/// The metrics configuration of an engine. Without the `metrics` feature this
+/// is empty, and every probe it hands out is a no-op.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MetricsConfig {
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Arc<EngineMetrics>>,
+    #[cfg(feature = "metrics")]
+    pub(crate) endpoint: Option<SocketAddr>,
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+impl MetricsConfig {
+    /// Returns a probe recording the metrics of the given component.
+    pub(crate) fn probe(&self, component: &str) -> Probe {
+        Probe {
+            #[cfg(feature = "metrics")]
+            labelled: self
+                .metrics
+                .clone()
+                .map(|metrics| (metrics, component.to_string())),
+        }
+    }
+
+    /// Starts serving the metrics if an endpoint is configured, until the
+    /// shutdown token is cancelled. Returns the address the endpoint is bound
+    /// to, which tells the port picked by the OS if the endpoint uses port 0.
+    pub(crate) fn serve(
+        &self,
+        shutdown: &tokio_util::sync::CancellationToken,
+    ) -> std::io::Result<Option<std::net::SocketAddr>> {
+        #[cfg(feature = "metrics")]
+        if let (Some(metrics), Some(addr)) = (&self.metrics, self.endpoint) {
+            // Bind before spawning the server, so that the engine fails to start
+            // if the endpoint is unavailable.
+            let listener = TcpListener::bind(addr)?;
+            let local_addr = listener.local_addr()?;
+            let registry = metrics.registry().clone();
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_listener(registry, listener, shutdown).await {
+                    error!("error serving metrics: {}", e);
+                }
+            });
+            return Ok(Some(local_addr));
+        }
+        Ok(None)
+    }
+}
+
+/// Records the metrics of a single engine component.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Probe {
+    #[cfg(feature = "metrics")]
+    labelled: Option<(Arc<EngineMetrics>, String)>,
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+impl Probe {
+    pub(crate) fn event_collected(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some((metrics, component)) = &self.labelled {
+            metrics
+                .collector_events
+                .with_label_values(&[component])
+                .inc();
+        }
+    }
+
+    pub(crate) fn event_processed(&self, latency: Duration, actions: usize) {
+        #[cfg(feature = "metrics")]
+        if let Some((metrics, component)) = &self.labelled {
+            metrics
+                .strategy_latency
+                .with_label_values(&[component])
+                .observe(latency.as_secs_f64());
+            metrics
+                .strategy_actions
+                .with_label_values(&[component])
+                .inc_by(actions as u64);
+        }
+    }
+
+    pub(crate) fn action_executed(&self, latency: Duration, success: bool) {
+        #[cfg(feature = "metrics")]
+        if let Some((metrics, component)) = &self.labelled {
+            let result = if success { "success" } else { "failure" };
+            metrics
+                .executor_results
+                .with_label_values(&[component, result])
+                .inc();
+            metrics
+                .executor_latency
+                .with_label_values(&[component])
+                .observe(latency.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn channel_depth(&self, depth: usize) {
+        #[cfg(feature = "metrics")]
+        if let Some((metrics, component)) = &self.labelled {
+            metrics
+                .channel_depth
+                .with_label_values(&[component])
+                .set(depth as i64);
+        }
+    }
+
+    pub(crate) fn dropped(&self, skipped: u64) {
+        #[cfg(feature = "metrics")]
+        if let Some((metrics, component)) = &self.labelled {
+            metrics
+                .channel_dropped
+                .with_label_values(&[component])
+                .inc_by(skipped);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+/// A report of how the components of an engine are wired together, returned by
+/// [Engine::describe](super::Engine::describe). Components without a name are
+/// named after their kind and index, e.g. `collector-0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineTopology {
+    pub collectors: Vec<CollectorInfo>,
+    pub strategies: Vec<StrategyInfo>,
+    pub executors: Vec<ExecutorInfo>,
+}
+
+/// A collector in an [EngineTopology](EngineTopology).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectorInfo {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+/// A strategy in an [EngineTopology](EngineTopology).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyInfo {
+    pub name: String,
+    pub tags: Vec<String>,
+    /// The collectors feeding the strategy.
+    pub collectors: Vec<String>,
+    /// The executors the strategy sends its actions to.
+    pub executors: Vec<String>,
+}
+
+/// An executor in an [EngineTopology](EngineTopology).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutorInfo {
+    pub name: String,
+    pub tags: Vec<String>,
+    /// Whether the executor only accepts the actions matching its filter.
+    pub filtered: bool,
+    /// The strategies sending actions to the executor.
+    pub strategies: Vec<String>,
+}
+
+impl EngineTopology {
+    /// Returns the first executor name used more than once, if any.
+    pub(crate) fn duplicate_executor(&self) -> Option<&str> {
+        self.executors
+            .iter()
+            .enumerate()
+            .find_map(|(index, executor)| {
+                self.executors[..index]
+                    .iter()
+                    .any(|other| other.name == executor.name)
+                    .then_some(executor.name.as_str())
+            })
+    }
+}
+
+/// Formats the topology with one component per line, e.g.
+///
+/// ```text
+/// collectors:
+///   blocks [mainnet]
+/// strategies:
+///   arb: blocks -> mempool
+/// executors:
+///   mempool (filtered): arb
+/// ```
+impl fmt::Display for EngineTopology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "collectors:")?;
+        for collector in &self.collectors {
+            writeln!(f, "  {}{}", collector.name, Tags(&collector.tags))?;
+        }
+        writeln!(f, "strategies:")?;
+        for strategy in &self.strategies {
+            writeln!(
+                f,
+                "  {}{}: {} -> {}",
+                strategy.name,
+                Tags(&strategy.tags),
+                List(&strategy.collectors),
+                List(&strategy.executors)
+            )?;
+        }
+        write!(f, "executors:")?;
+        for executor in &self.executors {
+            let filtered = if executor.filtered { " (filtered)" } else { "" };
+            write!(
+                f,
+                "\n  {}{}{}: {}",
+                executor.name,
+                Tags(&executor.tags),
+                filtered,
+                List(&executor.strategies)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats tags as ` [a, b]`, or nothing if there are none.
+struct Tags<'a>(&'a [String]);
+
+impl fmt::Display for Tags<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        write!(f, " [{}]", self.0.join(", "))
+    }
+}
+
+/// Formats names as `a, b`, or `none` if there are none.
+struct List<'a>(&'a [String]);
+
+impl fmt::Display for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "none");
+        }
+        write!(f, "{}", self.0.join(", "))
+    }
+}
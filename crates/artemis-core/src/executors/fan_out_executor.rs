@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::{
+    stream::{FuturesUnordered, StreamExt},
+    FutureExt,
+};
+use tracing::warn;
+
+use crate::types::{ExecutionOutcome, Executor, OutcomeReporter};
+
+/// An executor that sends every action to several executors concurrently, e.g.
+/// the same bundle to several relays. Wrap the inner executors in an
+/// [ExecutorMap](crate::types::ExecutorMap) to convert the action for each of
+/// them. Every inner executor reports its own outcomes, tagged with its name.
+pub struct FanOutExecutor<A> {
+    executors: Vec<(String, Arc<dyn Executor<A>>)>,
+    policy: FanOutPolicy,
+}
+
+/// When a [FanOutExecutor](FanOutExecutor) considers an action executed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FanOutPolicy {
+    /// Every executor must succeed.
+    AllMustSucceed,
+    /// At least one executor must succeed. The action is considered executed
+    /// as soon as one of them succeeds, while the others keep running in the
+    /// background.
+    FirstSuccess,
+    /// The action is always considered executed, and failures are only logged.
+    #[default]
+    BestEffort,
+}
+
+impl<A> FanOutExecutor<A> {
+    pub fn new(policy: FanOutPolicy) -> Self {
+        Self {
+            executors: vec![],
+            policy,
+        }
+    }
+
+    /// Adds an executor, whose name is used in logs, errors and outcomes.
+    pub fn with_executor(
+        mut self,
+        name: impl Into<String>,
+        executor: Box<dyn Executor<A>>,
+    ) -> Self {
+        self.executors.push((name.into(), Arc::from(executor)));
+        self
+    }
+}
+
+#[async_trait]
+impl<A> Executor<A> for FanOutExecutor<A>
+where
+    A: Clone + Send + Sync + 'static,
+{
+    async fn execute(&self, action: A) -> Result<()> {
+        self.execute_with_outcomes(action, OutcomeReporter::default())
+            .await
+    }
+
+    async fn execute_with_outcomes(&self, action: A, outcomes: OutcomeReporter) -> Result<()> {
+        // Executions are spawned so that they run to completion even once the
+        // policy no longer waits for them.
+        let mut executions: FuturesUnordered<_> = self
+            .executors
+            .iter()
+            .enumerate()
+            .map(|(index, (name, executor))| {
+                let name = name.clone();
+                let executor = executor.clone();
+                let outcomes = outcomes.with_executor(&name);
+                let action = action.clone();
+                tokio::spawn(async move {
+                    let result = executor.execute_with_outcomes(action, outcomes).await;
+                    if let Err(e) = &result {
+                        warn!("fan-out executor {} failed: {}", name, e);
+                    }
+                    result
+                })
+                .map(move |joined| (index, joined))
+            })
+            .collect();
+
+        let mut succeeded = 0;
+        let mut failures = vec![];
+        while let Some((index, joined)) = executions.next().await {
+            let name = &self.executors[index].0;
+            let result = joined.unwrap_or_else(|e| {
+                let reason = format!("executor panicked: {}", e);
+                outcomes
+                    .with_executor(name)
+                    .report(ExecutionOutcome::Failed {
+                        reason: reason.clone(),
+                    });
+                Err(anyhow!(reason))
+            });
+            match result {
+                Ok(()) => {
+                    succeeded += 1;
+                    if self.policy == FanOutPolicy::FirstSuccess {
+                        break;
+                    }
+                }
+                Err(e) => failures.push(format!("{}: {}", name, e)),
+            }
+        }
+
+        match self.policy {
+            FanOutPolicy::AllMustSucceed if !failures.is_empty() => Err(anyhow!(
+                "{} of {} executors failed: {}",
+                failures.len(),
+                self.executors.len(),
+                failures.join(", ")
+            )),
+            FanOutPolicy::FirstSuccess if succeeded == 0 => Err(anyhow!(
+                "all {} executors failed: {}",
+                self.executors.len(),
+                failures.join(", ")
+            )),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,15 +1,23 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use ethers::{
-    providers::Middleware, signers::Signer, types::transaction::eip2718::TypedTransaction,
+    providers::Middleware,
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, H256, U64},
+    utils::keccak256,
 };
 use ethers_flashbots::{BundleRequest, FlashbotsMiddleware};
 use reqwest::Url;
-use tracing::error;
+use tokio::time::{interval, timeout};
+use tracing::{error, warn};
 
-use crate::types::Executor;
+use crate::types::{ExecutionOutcome, Executor, OutcomeReporter};
+
+/// How long to wait for the target block of a bundle to be mined by default,
+/// before reporting its transactions as dropped.
+const DEFAULT_MAX_TARGET_BLOCK_WAIT: Duration = Duration::from_secs(60);
 
 /// A Flashbots executor that sends transactions to the Flashbots relay.
 pub struct FlashbotsExecutor<M, S> {
@@ -18,6 +26,9 @@ pub struct FlashbotsExecutor<M, S> {
 
     /// The signer to sign transactions before sending to the relay.
     tx_signer: S,
+
+    /// How long to wait for the target block of a bundle to be mined.
+    max_target_block_wait: Duration,
 }
 
 /// A bundle of transactions to send to the Flashbots relay.
@@ -29,32 +40,46 @@ impl<M: Middleware, S: Signer> FlashbotsExecutor<M, S> {
         Self {
             fb_client,
             tx_signer,
+            max_target_block_wait: DEFAULT_MAX_TARGET_BLOCK_WAIT,
         }
     }
+
+    /// Set how long to wait for the target block of a bundle to be mined. If
+    /// it is not mined by then, e.g. because the node stopped syncing, the
+    /// transactions of the bundle are reported as
+    /// [dropped](ExecutionOutcome::Dropped), and no longer watched.
+    pub fn with_max_target_block_wait(mut self, max_wait: Duration) -> Self {
+        self.max_target_block_wait = max_wait;
+        self
+    }
 }
 
-#[async_trait]
-impl<M, S> Executor<FlashbotsBundle> for FlashbotsExecutor<M, S>
+impl<M, S> FlashbotsExecutor<M, S>
 where
     M: Middleware + 'static,
     M::Error: 'static,
     S: Signer + 'static,
 {
-    /// Send a bundle to transactions to the Flashbots relay.
-    async fn execute(&self, action: FlashbotsBundle) -> Result<()> {
+    /// Sign and simulate a bundle, returning it along with the hashes of its
+    /// transactions and the block it targets.
+    async fn prepare(&self, action: FlashbotsBundle) -> Result<(BundleRequest, Vec<H256>, U64)> {
         // Add txs to bundle.
         let mut bundle = BundleRequest::new();
+        let mut tx_hashes = Vec::with_capacity(action.len());
 
         // Sign each transaction in bundle.
         for tx in action {
             let signature = self.tx_signer.sign_transaction(&tx).await?;
-            bundle.add_transaction(tx.rlp_signed(&signature));
+            let signed_tx = tx.rlp_signed(&signature);
+            tx_hashes.push(H256::from(keccak256(&signed_tx)));
+            bundle.add_transaction(signed_tx);
         }
 
         // Simulate bundle.
         let block_number = self.fb_client.get_block_number().await?;
+        let target_block = block_number + 1;
         let bundle = bundle
-            .set_block(block_number + 1)
+            .set_block(target_block)
             .set_simulation_block(block_number)
             .set_simulation_timestamp(0);
 
@@ -64,13 +89,140 @@ where
             error!("Error simulating bundle: {:?}", simulate_error);
         }
 
+        Ok((bundle, tx_hashes, target_block))
+    }
+}
+
+#[async_trait]
+impl<M, S> Executor<FlashbotsBundle> for FlashbotsExecutor<M, S>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+    S: Signer + 'static,
+{
+    /// Send a bundle to transactions to the Flashbots relay.
+    async fn execute(&self, action: FlashbotsBundle) -> Result<()> {
+        let (bundle, _, _) = self.prepare(action).await?;
+
+        // Send bundle.
+        let pending_bundle = self.fb_client.send_bundle(&bundle).await;
+
+        if let Err(send_error) = pending_bundle {
+            error!("Error sending bundle: {:?}", send_error);
+        }
+
+        Ok(())
+    }
+
+    /// Send a bundle to the Flashbots relay, then check whether it landed in
+    /// the target block in the background, for at most
+    /// [max_target_block_wait](FlashbotsExecutor::with_max_target_block_wait).
+    /// A bundle the relay rejects is reported as
+    /// [failed](ExecutionOutcome::Failed).
+    async fn execute_with_outcomes(
+        &self,
+        action: FlashbotsBundle,
+        outcomes: OutcomeReporter,
+    ) -> Result<()> {
+        let (bundle, tx_hashes, target_block) = match self.prepare(action).await {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                outcomes.report(ExecutionOutcome::Failed {
+                    reason: e.to_string(),
+                });
+                return Err(e);
+            }
+        };
+
         // Send bundle.
         let pending_bundle = self.fb_client.send_bundle(&bundle).await;
 
         if let Err(send_error) = pending_bundle {
             error!("Error sending bundle: {:?}", send_error);
+            outcomes.report(ExecutionOutcome::Failed {
+                reason: format!("Error sending bundle: {:?}", send_error),
+            });
+            return Ok(());
         }
 
+        outcomes.report(ExecutionOutcome::Submitted {
+            tx_hashes: tx_hashes.clone(),
+        });
+
+        let client = self.fb_client.inner().clone();
+        let max_wait = self.max_target_block_wait;
+        tokio::spawn(async move {
+            if timeout(max_wait, target_block_mined(client.as_ref(), target_block))
+                .await
+                .is_err()
+            {
+                warn!(
+                    "Block {} not mined, no longer watching bundle",
+                    target_block
+                );
+                tx_hashes
+                    .into_iter()
+                    .for_each(|tx_hash| outcomes.report(ExecutionOutcome::Dropped { tx_hash }));
+                return;
+            }
+            match bundle_outcomes(client.as_ref(), tx_hashes, target_block).await {
+                Ok(tx_outcomes) => tx_outcomes.into_iter().for_each(|o| outcomes.report(o)),
+                Err(e) => error!("Error watching bundle: {}", e),
+            }
+        });
         Ok(())
     }
 }
+
+/// Waits until the target block of a bundle is mined, polling the block number
+/// at the provider's interval.
+async fn target_block_mined<M: Middleware>(client: &M, target_block: U64) {
+    let mut ticks = interval(client.provider().get_interval());
+    loop {
+        ticks.tick().await;
+        match client.get_block_number().await {
+            Ok(block) if block >= target_block => return,
+            Ok(_) => {}
+            Err(e) => warn!("Error getting block number: {}", e),
+        }
+    }
+}
+
+/// Returns the outcome of each transaction of a bundle once its target block
+/// is mined, or a single [ExecutionOutcome::BundleNotIncluded] if the bundle
+/// did not land in that block.
+async fn bundle_outcomes<M: Middleware>(
+    client: &M,
+    tx_hashes: Vec<H256>,
+    target_block: U64,
+) -> Result<Vec<ExecutionOutcome>>
+where
+    M::Error: 'static,
+{
+    let mut outcomes = Vec::with_capacity(tx_hashes.len());
+    for tx_hash in &tx_hashes {
+        let receipt = client.get_transaction_receipt(*tx_hash).await?;
+        match receipt {
+            Some(receipt) if receipt.block_number == Some(target_block) => {
+                if receipt.status == Some(U64::from(1)) {
+                    outcomes.push(ExecutionOutcome::Included {
+                        tx_hash: *tx_hash,
+                        block: target_block,
+                    });
+                } else {
+                    outcomes.push(ExecutionOutcome::Reverted {
+                        tx_hash: *tx_hash,
+                        block: target_block,
+                    });
+                }
+            }
+            _ => {
+                return Ok(vec![ExecutionOutcome::BundleNotIncluded {
+                    tx_hashes,
+                    block: target_block,
+                }])
+            }
+        }
+    }
+    Ok(outcomes)
+}
@@ -3,17 +3,24 @@ use std::{
     sync::Arc,
 };
 
-use crate::types::Executor;
+use crate::types::{ExecutionOutcome, Executor, OutcomeReporter};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use ethers::{
-    providers::Middleware,
-    types::{transaction::eip2718::TypedTransaction, U256},
+    providers::{Middleware, PendingTransaction},
+    types::{transaction::eip2718::TypedTransaction, TxHash, U256, U64},
 };
+use tokio::time::interval;
+use tracing::{error, warn};
+
+/// The default number of blocks after which a transaction which is still
+/// pending is reported as dropped.
+const DEFAULT_MAX_PENDING_BLOCKS: u64 = 25;
 
 /// An executor that sends transactions to the mempool.
 pub struct MempoolExecutor<M> {
     client: Arc<M>,
+    max_pending_blocks: u64,
 }
 
 /// Information about the gas bid for a transaction.
@@ -34,18 +41,28 @@ pub struct SubmitTxToMempool {
 
 impl<M: Middleware> MempoolExecutor<M> {
     pub fn new(client: Arc<M>) -> Self {
-        Self { client }
+        Self {
+            client,
+            max_pending_blocks: DEFAULT_MAX_PENDING_BLOCKS,
+        }
+    }
+
+    /// Sets the number of blocks after which a transaction which is neither
+    /// included nor dropped is reported as [dropped](ExecutionOutcome::Dropped),
+    /// and no longer watched.
+    pub fn with_max_pending_blocks(mut self, blocks: u64) -> Self {
+        self.max_pending_blocks = blocks;
+        self
     }
 }
 
-#[async_trait]
-impl<M> Executor<SubmitTxToMempool> for MempoolExecutor<M>
+impl<M> MempoolExecutor<M>
 where
     M: Middleware,
     M::Error: 'static,
 {
-    /// Send a transaction to the mempool.
-    async fn execute(&self, mut action: SubmitTxToMempool) -> Result<()> {
+    /// Price and send a transaction to the mempool, returning its hash.
+    async fn send(&self, mut action: SubmitTxToMempool) -> Result<TxHash> {
         let gas_usage = self
             .client
             .estimate_gas(&action.tx, None)
@@ -68,7 +85,91 @@ where
                 .context("Error getting gas price: {}")?;
         }
         action.tx.set_gas_price(bid_gas_price);
-        self.client.send_transaction(action.tx, None).await?;
+        let pending_tx = self.client.send_transaction(action.tx, None).await?;
+        Ok(pending_tx.tx_hash())
+    }
+}
+
+#[async_trait]
+impl<M> Executor<SubmitTxToMempool> for MempoolExecutor<M>
+where
+    M: Middleware + 'static,
+    M::Error: 'static,
+{
+    /// Send a transaction to the mempool.
+    async fn execute(&self, action: SubmitTxToMempool) -> Result<()> {
+        self.send(action).await?;
+        Ok(())
+    }
+
+    /// Send a transaction to the mempool, then watch for it to be included in
+    /// a block in the background, for at most
+    /// [max_pending_blocks](MempoolExecutor::with_max_pending_blocks) blocks.
+    async fn execute_with_outcomes(
+        &self,
+        action: SubmitTxToMempool,
+        outcomes: OutcomeReporter,
+    ) -> Result<()> {
+        let tx_hash = match self.send(action).await {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                outcomes.report(ExecutionOutcome::Failed {
+                    reason: e.to_string(),
+                });
+                return Err(e);
+            }
+        };
+        outcomes.report(ExecutionOutcome::Submitted {
+            tx_hashes: vec![tx_hash],
+        });
+
+        let client = self.client.clone();
+        let max_pending_blocks = self.max_pending_blocks;
+        tokio::spawn(async move {
+            let pending = PendingTransaction::new(tx_hash, client.provider());
+            let receipt = tokio::select! {
+                receipt = pending => receipt,
+                _ = blocks_mined(&*client, max_pending_blocks) => {
+                    warn!("Transaction {:?} still pending, no longer watching it", tx_hash);
+                    Ok(None)
+                }
+            };
+            let outcome = match receipt {
+                Ok(Some(receipt)) => {
+                    let block = receipt.block_number.unwrap_or_default();
+                    if receipt.status == Some(U64::from(1)) {
+                        ExecutionOutcome::Included { tx_hash, block }
+                    } else {
+                        ExecutionOutcome::Reverted { tx_hash, block }
+                    }
+                }
+                Ok(None) => ExecutionOutcome::Dropped { tx_hash },
+                Err(e) => {
+                    error!("Error watching transaction {:?}: {}", tx_hash, e);
+                    return;
+                }
+            };
+            outcomes.report(outcome);
+        });
         Ok(())
     }
 }
+
+/// Waits until `blocks` blocks were mined after the current one, polling the
+/// block number at the provider's interval.
+async fn blocks_mined<M: Middleware>(client: &M, blocks: u64) {
+    let mut ticks = interval(client.provider().get_interval());
+    let mut deadline = None;
+    loop {
+        ticks.tick().await;
+        match client.get_block_number().await {
+            Ok(block) => {
+                let deadline = *deadline.get_or_insert(block + blocks);
+                if block >= deadline {
+                    return;
+                }
+            }
+            Err(e) => warn!("Error getting block number: {}", e),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::types::Executor;
+use crate::types::{ExecutionOutcome, Executor, OutcomeReporter};
 use anyhow::Result;
 use async_trait::async_trait;
 use ethers::signers::Signer;
@@ -43,4 +43,27 @@ impl Executor<SendBundleRequest> for MevshareExecutor {
         };
         Ok(())
     }
+
+    /// Send bundles to the matchmaker, reporting whether the matchmaker
+    /// accepted them.
+    async fn execute_with_outcomes(
+        &self,
+        action: SendBundleRequest,
+        outcomes: OutcomeReporter,
+    ) -> Result<()> {
+        let body = self.mev_share_client.send_bundle(action).await;
+        match body {
+            Ok(body) => {
+                info!("Bundle response: {:?}", body);
+                outcomes.report(ExecutionOutcome::Submitted { tx_hashes: vec![] });
+            }
+            Err(e) => {
+                error!("Bundle error: {}", e);
+                outcomes.report(ExecutionOutcome::Failed {
+                    reason: e.to_string(),
+                });
+            }
+        };
+        Ok(())
+    }
 }
@@ -10,3 +10,6 @@ pub mod mempool_executor;
 
 /// This executor submits bundles to the flashbots matchmaker.
 pub mod mev_share_executor;
+
+/// This executor sends actions to several executors concurrently.
+pub mod fan_out_executor;
@@ -1,15 +1,26 @@
+/// This module contains the [CollectorExt](CollectorExt) adapters.
+mod collector_ext;
+
 use anyhow::Result;
 use async_trait::async_trait;
-use ethers::types::Transaction;
+use ethers::types::{Transaction, H256, U64};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::pin::Pin;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
 
 use crate::collectors::block_collector::NewBlock;
-use crate::collectors::opensea_order_collector::OpenseaOrder;
+#[allow(deprecated)]
+use crate::collectors::opensea_order_collector::{OpenseaEvent, OpenseaOrder};
 use crate::executors::flashbots_executor::FlashbotsBundle;
 use crate::executors::mempool_executor::SubmitTxToMempool;
 
+pub use self::collector_ext::{
+    CollectorBatch, CollectorDebounce, CollectorDedup, CollectorExt, CollectorFilterMap,
+    CollectorMerge, CollectorThrottle,
+};
+
 /// A stream of events emitted by a [Collector](Collector).
 pub type CollectorStream<'a, E> = Pin<Box<dyn Stream<Item = E> + Send + 'a>>;
 
@@ -27,8 +38,111 @@ pub trait Strategy<E, A>: Send + Sync {
     /// onchain data.
     async fn sync_state(&mut self) -> Result<()>;
 
+    /// Called once the strategy is in sync, before it processes any event.
+    /// Returns the actions to execute on startup, if any.
+    async fn on_start(&mut self) -> Vec<A> {
+        vec![]
+    }
+
     /// Process an event, and return an action if needed.
     async fn process_event(&mut self, event: E) -> Vec<A>;
+
+    /// Process the outcome of executing one of the actions returned by this
+    /// strategy, identified by its [id](ActionId), and return follow-up
+    /// actions if needed.
+    async fn on_execution_outcome(&mut self, _outcome: ActionOutcome) -> Vec<A> {
+        vec![]
+    }
+
+    /// Report a condition the strategy cannot recover from on its own. The
+    /// engine checks for a fault after every event and execution outcome the
+    /// strategy processes, and handles it according to its
+    /// [action](FaultAction).
+    fn take_fault(&mut self) -> Option<StrategyFault> {
+        None
+    }
+
+    /// Sync the state of the strategy again, when it reports a
+    /// [resync](FaultAction::Resync) fault or on its resync interval. Events
+    /// received in the meantime are processed once it completes. Defaults to
+    /// [sync_state](Strategy::sync_state).
+    async fn resync(&mut self) -> Result<()> {
+        self.sync_state().await
+    }
+
+    /// Called once the strategy stops receiving events, either because the
+    /// engine is shutting down or because the strategy was disabled, e.g. to
+    /// flush its state. Returns the actions to execute before the engine stops,
+    /// e.g. to cancel outstanding bundles.
+    async fn on_shutdown(&mut self) -> Vec<A> {
+        vec![]
+    }
+}
+
+/// Snapshot trait, implemented by strategies whose state can be saved to disk
+/// and restored on startup, so that they only need to catch up from the block
+/// at which the snapshot was taken instead of resyncing from scratch. See
+/// [SnapshotStrategy](crate::utilities::snapshot::SnapshotStrategy).
+pub trait Snapshot {
+    /// The state of the strategy which is saved in snapshots.
+    type State: Serialize + DeserializeOwned;
+
+    /// Take a snapshot of the current state, or return `None` if the strategy
+    /// has not synced any state yet.
+    fn snapshot(&self) -> Option<StateSnapshot<Self::State>>;
+
+    /// Restore a snapshot before the state is synced. The strategy should then
+    /// only sync the state from the snapshot's block onwards.
+    fn restore(&mut self, snapshot: StateSnapshot<Self::State>);
+}
+
+/// The state of a strategy, along with the last block it is in sync with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot<S> {
+    pub block_number: u64,
+    pub state: S,
+}
+
+/// A fatal condition reported by a strategy, e.g. because its state is out of
+/// sync with the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyFault {
+    pub action: FaultAction,
+    pub reason: String,
+}
+
+/// How the engine handles a [StrategyFault](StrategyFault).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FaultAction {
+    /// Stop the faulty strategy, and keep running the others.
+    #[default]
+    Disable,
+    /// Sync the state of the strategy again, replaying the events received in
+    /// the meantime. The strategy is disabled if it fails to sync.
+    Resync,
+    /// Gracefully shut down the whole engine.
+    Stop,
+}
+
+impl StrategyFault {
+    pub fn new(action: FaultAction, reason: impl Into<String>) -> Self {
+        Self {
+            action,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn disable(reason: impl Into<String>) -> Self {
+        Self::new(FaultAction::Disable, reason)
+    }
+
+    pub fn resync(reason: impl Into<String>) -> Self {
+        Self::new(FaultAction::Resync, reason)
+    }
+
+    pub fn stop(reason: impl Into<String>) -> Self {
+        Self::new(FaultAction::Stop, reason)
+    }
 }
 
 /// Executor trait, responsible for executing actions returned by strategies.
@@ -36,6 +150,118 @@ pub trait Strategy<E, A>: Send + Sync {
 pub trait Executor<A>: Send + Sync {
     /// Execute an action.
     async fn execute(&self, action: A) -> Result<()>;
+
+    /// Execute an action, reporting its outcomes to the strategy that produced
+    /// it. By default, the action is reported as submitted if it was executed
+    /// successfully, and as failed otherwise.
+    async fn execute_with_outcomes(&self, action: A, outcomes: OutcomeReporter) -> Result<()>
+    where
+        A: Send + 'async_trait,
+    {
+        let result = self.execute(action).await;
+        match &result {
+            Ok(_) => outcomes.report(ExecutionOutcome::Submitted { tx_hashes: vec![] }),
+            Err(e) => outcomes.report(ExecutionOutcome::Failed {
+                reason: e.to_string(),
+            }),
+        }
+        result
+    }
+}
+
+/// The outcome of executing an action, reported by executors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    /// The action was submitted, e.g. to the mempool or to a relay, along with
+    /// the hashes of its transactions if they are known.
+    Submitted { tx_hashes: Vec<H256> },
+    /// A transaction was included in a block.
+    Included { tx_hash: H256, block: U64 },
+    /// A transaction was included in a block, but reverted.
+    Reverted { tx_hash: H256, block: U64 },
+    /// A transaction was dropped before it was included, or is still pending
+    /// after the executor gave up watching it.
+    Dropped { tx_hash: H256 },
+    /// A bundle was not included in its target block.
+    BundleNotIncluded { tx_hashes: Vec<H256>, block: U64 },
+    /// The action could not be executed.
+    Failed { reason: String },
+}
+
+/// Identifies an action returned by a strategy. The engine numbers the actions
+/// of each strategy from 0, in the order the strategy returns them from any of
+/// its hooks, so a strategy can tell which of its actions an
+/// [outcome](ActionOutcome) belongs to by counting the actions it returns.
+pub type ActionId = u64;
+
+/// An [outcome](ExecutionOutcome) along with the action it belongs to and the
+/// executor which reported it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionOutcome {
+    pub action_id: ActionId,
+    /// The name of the executor, e.g. `executor-0` or the name it was added
+    /// with. Executors nested in another one, such as the executors of a
+    /// [FanOutExecutor](crate::executors::fan_out_executor::FanOutExecutor),
+    /// are named after both, e.g. `relays/flashbots`.
+    pub executor: Option<String>,
+    pub outcome: ExecutionOutcome,
+}
+
+/// Reports [outcomes](ExecutionOutcome) of an action back to the strategy that
+/// produced it. Executors can clone it to report outcomes that are only known
+/// later, e.g. once a transaction is included.
+#[derive(Debug, Clone, Default)]
+pub struct OutcomeReporter {
+    sender: Option<UnboundedSender<ActionOutcome>>,
+    action_id: ActionId,
+    executor: Option<String>,
+}
+
+impl OutcomeReporter {
+    pub fn new(sender: UnboundedSender<ActionOutcome>) -> Self {
+        Self {
+            sender: Some(sender),
+            action_id: 0,
+            executor: None,
+        }
+    }
+
+    /// Returns a reporter for the outcomes of another action.
+    pub fn with_action_id(&self, action_id: ActionId) -> Self {
+        Self {
+            action_id,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a reporter for the outcomes reported by an executor, nested in
+    /// the executor of this reporter if it has one.
+    pub fn with_executor(&self, name: &str) -> Self {
+        let executor = match &self.executor {
+            Some(parent) => format!("{}/{}", parent, name),
+            None => name.to_string(),
+        };
+        Self {
+            executor: Some(executor),
+            ..self.clone()
+        }
+    }
+
+    /// The id of the action whose outcomes are reported.
+    pub fn action_id(&self) -> ActionId {
+        self.action_id
+    }
+
+    /// Report an outcome. Outcomes are discarded if the strategy has stopped.
+    pub fn report(&self, outcome: ExecutionOutcome) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(ActionOutcome {
+                action_id: self.action_id,
+                executor: self.executor.clone(),
+                outcome,
+            });
+        }
+    }
 }
 
 /// CollectorMap is a wrapper around a [Collector](Collector) that maps outgoing
@@ -65,6 +291,72 @@ where
     }
 }
 
+/// StrategyMap is a wrapper around a [Strategy](Strategy) that filters and maps
+/// incoming events to the strategy's event type, and maps outgoing actions to a
+/// different type. Events for which the event function returns `None` are not
+/// delivered to the strategy.
+pub struct StrategyMap<E, A, F, G> {
+    strategy: Box<dyn Strategy<E, A>>,
+    event_fn: F,
+    action_fn: G,
+}
+
+impl<E, A, F, G> StrategyMap<E, A, F, G> {
+    pub fn new(strategy: Box<dyn Strategy<E, A>>, event_fn: F, action_fn: G) -> Self {
+        Self {
+            strategy,
+            event_fn,
+            action_fn,
+        }
+    }
+}
+
+#[async_trait]
+impl<E1, E2, A1, A2, F, G> Strategy<E2, A2> for StrategyMap<E1, A1, F, G>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    A1: Send + Sync + 'static,
+    A2: Send + Sync + 'static,
+    F: Fn(E2) -> Option<E1> + Send + Sync + Clone + 'static,
+    G: Fn(A1) -> A2 + Send + Sync + Clone + 'static,
+{
+    async fn sync_state(&mut self) -> Result<()> {
+        self.strategy.sync_state().await
+    }
+
+    async fn on_start(&mut self) -> Vec<A2> {
+        let actions = self.strategy.on_start().await;
+        actions.into_iter().map(&self.action_fn).collect()
+    }
+
+    async fn process_event(&mut self, event: E2) -> Vec<A2> {
+        let Some(event) = (self.event_fn)(event) else {
+            return vec![];
+        };
+        let actions = self.strategy.process_event(event).await;
+        actions.into_iter().map(&self.action_fn).collect()
+    }
+
+    async fn on_execution_outcome(&mut self, outcome: ActionOutcome) -> Vec<A2> {
+        let actions = self.strategy.on_execution_outcome(outcome).await;
+        actions.into_iter().map(&self.action_fn).collect()
+    }
+
+    fn take_fault(&mut self) -> Option<StrategyFault> {
+        self.strategy.take_fault()
+    }
+
+    async fn resync(&mut self) -> Result<()> {
+        self.strategy.resync().await
+    }
+
+    async fn on_shutdown(&mut self) -> Vec<A2> {
+        let actions = self.strategy.on_shutdown().await;
+        actions.into_iter().map(&self.action_fn).collect()
+    }
+}
+
 /// ExecutorMap is a wrapper around an [Executor](Executor) that maps incoming
 /// actions to a different type.
 pub struct ExecutorMap<A, F> {
@@ -92,12 +384,25 @@ where
             None => Ok(()),
         }
     }
+
+    async fn execute_with_outcomes(&self, action: A1, outcomes: OutcomeReporter) -> Result<()> {
+        let action = (self.f)(action);
+        match action {
+            Some(action) => self.executor.execute_with_outcomes(action, outcomes).await,
+            None => Ok(()),
+        }
+    }
 }
 
 /// Convenience enum containing all the events that can be emitted by collectors.
+#[allow(deprecated)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Events {
     NewBlock(NewBlock),
     Transaction(Transaction),
+    OpenseaEvent(Box<OpenseaEvent>),
+    #[deprecated(note = "use Events::OpenseaEvent instead")]
+    #[serde(skip)]
     OpenseaOrder(Box<OpenseaOrder>),
 }
 
@@ -0,0 +1,281 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use tokio::time::{timeout, timeout_at, Instant};
+
+use super::{Collector, CollectorMap, CollectorStream};
+
+/// Adapters for transforming the event stream of a [Collector](Collector),
+/// implemented for every collector.
+///
+/// ```ignore
+/// let txs = MempoolCollector::new(provider.clone())
+///     .merge(MempoolCollector::new(backup_provider.clone()))
+///     .dedup_by_key(|tx| tx.hash, Duration::from_secs(60))
+///     .filter_map(|tx| tx.to.map(|_| Event::Transaction(tx)));
+/// ```
+pub trait CollectorExt<E>: Collector<E> + Sized + 'static {
+    /// Maps every event to a different type.
+    fn map<E2, F>(self, f: F) -> CollectorMap<E, F>
+    where
+        F: Fn(E) -> E2,
+    {
+        CollectorMap::new(Box::new(self), f)
+    }
+
+    /// Maps every event to a different type, dropping the events for which
+    /// `f` returns `None`.
+    fn filter_map<E2, F>(self, f: F) -> CollectorFilterMap<E, F>
+    where
+        F: Fn(E) -> Option<E2>,
+    {
+        CollectorFilterMap {
+            collector: Box::new(self),
+            f,
+        }
+    }
+
+    /// Drops the events whose key was already seen within `ttl`, e.g. the
+    /// same transaction hash received from several sources.
+    fn dedup_by_key<K, F>(self, key: F, ttl: Duration) -> CollectorDedup<E, F>
+    where
+        F: Fn(&E) -> K,
+    {
+        CollectorDedup {
+            collector: Box::new(self),
+            key,
+            ttl,
+        }
+    }
+
+    /// Emits at most one event per `period`, dropping the events received in
+    /// between.
+    fn throttle(self, period: Duration) -> CollectorThrottle<E> {
+        CollectorThrottle {
+            collector: Box::new(self),
+            period,
+        }
+    }
+
+    /// Emits the latest event once no new event has been received for
+    /// `period`, dropping the events it replaces.
+    fn debounce(self, period: Duration) -> CollectorDebounce<E> {
+        CollectorDebounce {
+            collector: Box::new(self),
+            period,
+        }
+    }
+
+    /// Merges the events of both collectors into a single stream, in the order
+    /// in which they are received.
+    fn merge<C: Collector<E> + 'static>(self, other: C) -> CollectorMerge<E> {
+        CollectorMerge::new(vec![Box::new(self), Box::new(other)])
+    }
+
+    /// Groups events into batches of up to `size` events, emitting a batch
+    /// once it is full or `timeout` after its first event was received.
+    fn batch(self, size: usize, timeout: Duration) -> CollectorBatch<E> {
+        CollectorBatch {
+            collector: Box::new(self),
+            size,
+            timeout,
+        }
+    }
+}
+
+impl<E, C: Collector<E> + 'static> CollectorExt<E> for C {}
+
+/// CollectorFilterMap is a wrapper around a [Collector](Collector) that maps
+/// outgoing events to a different type, dropping some of them. See
+/// [CollectorExt::filter_map](CollectorExt::filter_map).
+pub struct CollectorFilterMap<E, F> {
+    collector: Box<dyn Collector<E>>,
+    f: F,
+}
+
+#[async_trait]
+impl<E1, E2, F> Collector<E2> for CollectorFilterMap<E1, F>
+where
+    E1: Send + Sync + 'static,
+    E2: Send + Sync + 'static,
+    F: Fn(E1) -> Option<E2> + Send + Sync + Clone + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E2>> {
+        let stream = self.collector.get_event_stream().await?;
+        let f = self.f.clone();
+        let stream = stream.filter_map(move |event| futures::future::ready(f(event)));
+        Ok(Box::pin(stream))
+    }
+}
+
+/// CollectorDedup is a wrapper around a [Collector](Collector) that drops
+/// duplicate events. See [CollectorExt::dedup_by_key](CollectorExt::dedup_by_key).
+pub struct CollectorDedup<E, F> {
+    collector: Box<dyn Collector<E>>,
+    key: F,
+    ttl: Duration,
+}
+
+#[async_trait]
+impl<E, K, F> Collector<E> for CollectorDedup<E, F>
+where
+    E: Send + Sync + 'static,
+    K: Hash + Eq + Clone + Send + 'static,
+    F: Fn(&E) -> K + Send + Sync + Clone + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?;
+        let key = self.key.clone();
+        let ttl = self.ttl;
+
+        // Keys are expired in the order in which they were seen.
+        let mut seen: HashMap<K, Instant> = HashMap::new();
+        let mut expiries: VecDeque<(Instant, K)> = VecDeque::new();
+        let stream = stream.filter(move |event| {
+            let now = Instant::now();
+            while let Some((seen_at, _)) = expiries.front() {
+                if now.duration_since(*seen_at) < ttl {
+                    break;
+                }
+                let (seen_at, expired) = expiries.pop_front().unwrap();
+                if seen.get(&expired) == Some(&seen_at) {
+                    seen.remove(&expired);
+                }
+            }
+
+            let key = key(event);
+            let fresh = !seen.contains_key(&key);
+            if fresh {
+                seen.insert(key.clone(), now);
+                expiries.push_back((now, key));
+            }
+            futures::future::ready(fresh)
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// CollectorThrottle is a wrapper around a [Collector](Collector) that limits
+/// the rate of events. See [CollectorExt::throttle](CollectorExt::throttle).
+pub struct CollectorThrottle<E> {
+    collector: Box<dyn Collector<E>>,
+    period: Duration,
+}
+
+#[async_trait]
+impl<E> Collector<E> for CollectorThrottle<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?;
+        let period = self.period;
+        let mut last_emitted: Option<Instant> = None;
+        let stream = stream.filter(move |_| {
+            let now = Instant::now();
+            let emit = match last_emitted {
+                Some(last_emitted) => now.duration_since(last_emitted) >= period,
+                None => true,
+            };
+            if emit {
+                last_emitted = Some(now);
+            }
+            futures::future::ready(emit)
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// CollectorDebounce is a wrapper around a [Collector](Collector) that only
+/// emits an event once the stream has settled. See
+/// [CollectorExt::debounce](CollectorExt::debounce).
+pub struct CollectorDebounce<E> {
+    collector: Box<dyn Collector<E>>,
+    period: Duration,
+}
+
+#[async_trait]
+impl<E> Collector<E> for CollectorDebounce<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?.fuse();
+        let period = self.period;
+        let stream = stream::unfold(stream, move |mut stream| async move {
+            let mut latest = stream.next().await?;
+            while let Ok(Some(event)) = timeout(period, stream.next()).await {
+                latest = event;
+            }
+            Some((latest, stream))
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// CollectorMerge merges the events of several [Collectors](Collector) into a
+/// single stream. See [CollectorExt::merge](CollectorExt::merge).
+pub struct CollectorMerge<E> {
+    collectors: Vec<Box<dyn Collector<E>>>,
+}
+
+impl<E> CollectorMerge<E> {
+    pub fn new(collectors: Vec<Box<dyn Collector<E>>>) -> Self {
+        Self { collectors }
+    }
+}
+
+/// The merged stream ends once every stream has ended, and fails to start if
+/// any of the collectors does.
+#[async_trait]
+impl<E> Collector<E> for CollectorMerge<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let mut streams = Vec::with_capacity(self.collectors.len());
+        for collector in &self.collectors {
+            streams.push(collector.get_event_stream().await?);
+        }
+        Ok(Box::pin(stream::select_all(streams)))
+    }
+}
+
+/// CollectorBatch is a wrapper around a [Collector](Collector) that groups
+/// events into batches. See [CollectorExt::batch](CollectorExt::batch).
+pub struct CollectorBatch<E> {
+    collector: Box<dyn Collector<E>>,
+    size: usize,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl<E> Collector<Vec<E>> for CollectorBatch<E>
+where
+    E: Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, Vec<E>>> {
+        if self.size == 0 {
+            return Err(anyhow!("collector batch size must be non-zero"));
+        }
+
+        let stream = self.collector.get_event_stream().await?.fuse();
+        let (size, batch_timeout) = (self.size, self.timeout);
+        let stream = stream::unfold(stream, move |mut stream| async move {
+            let mut batch = vec![stream.next().await?];
+            let deadline = Instant::now() + batch_timeout;
+            while batch.len() < size {
+                match timeout_at(deadline, stream.next()).await {
+                    Ok(Some(event)) => batch.push(event),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            Some((batch, stream))
+        });
+        Ok(Box::pin(stream))
+    }
+}
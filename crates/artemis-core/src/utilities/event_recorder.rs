@@ -0,0 +1,137 @@
+use std::{
+    fs::OpenOptions,
+    io::{LineWriter, Write},
+    path::Path,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::StreamExt;
+use tracing::error;
+
+use crate::types::{Collector, CollectorStream};
+
+/// An event recorded by an [EventRecorder](EventRecorder), along with the time
+/// at which it was recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEvent<E> {
+    /// Milliseconds since the unix epoch.
+    pub timestamp_ms: u64,
+    pub event: E,
+}
+
+/// A request to the writer thread of an [EventRecorder](EventRecorder).
+#[derive(Debug)]
+enum WriterCommand {
+    /// Appends a serialized event to the file.
+    Write(Vec<u8>),
+    /// Flushes the file, and acknowledges once every earlier line is written.
+    Flush(oneshot::Sender<std::io::Result<()>>),
+}
+
+/// Appends events to a JSONL file, one [RecordedEvent](RecordedEvent) per line,
+/// so that they can be replayed with a
+/// [ReplayCollector](crate::collectors::replay_collector::ReplayCollector).
+/// Events are serialized when recorded, and written by a dedicated thread so
+/// that recording never blocks the async runtime. The thread stops once every
+/// clone of the recorder is dropped.
+#[derive(Debug, Clone)]
+pub struct EventRecorder {
+    sender: mpsc::UnboundedSender<WriterCommand>,
+}
+
+impl EventRecorder {
+    /// Opens the file at the given path for appending, creating it if needed.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())?;
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        thread::Builder::new()
+            .name("event-recorder".into())
+            .spawn(move || {
+                let mut writer = LineWriter::new(file);
+                while let Some(command) = receiver.blocking_recv() {
+                    match command {
+                        WriterCommand::Write(line) => {
+                            if let Err(e) = writer.write_all(&line) {
+                                error!("error recording event: {}", e);
+                            }
+                        }
+                        WriterCommand::Flush(ack) => {
+                            let _ = ack.send(writer.flush());
+                        }
+                    }
+                }
+            })?;
+        Ok(Self { sender })
+    }
+
+    /// Queues an event to be appended to the file, timestamped with the current
+    /// time.
+    pub fn record<E: Serialize>(&self, event: &E) -> Result<()> {
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let mut line = serde_json::to_vec(&RecordedEvent {
+            timestamp_ms,
+            event,
+        })?;
+        line.push(b'\n');
+
+        self.sender
+            .send(WriterCommand::Write(line))
+            .map_err(|_| anyhow!("event recorder stopped"))
+    }
+
+    /// Waits until every event recorded so far has been written to the file.
+    pub async fn flush(&self) -> Result<()> {
+        let (ack, done) = oneshot::channel();
+        self.sender
+            .send(WriterCommand::Flush(ack))
+            .map_err(|_| anyhow!("event recorder stopped"))?;
+        done.await
+            .map_err(|_| anyhow!("event recorder stopped"))??;
+        Ok(())
+    }
+}
+
+/// RecordingCollector is a wrapper around a [Collector](Collector) that records
+/// every event emitted by the inner collector with an
+/// [EventRecorder](EventRecorder). Wrap collectors once their events have been
+/// mapped to the engine's event type, so that the whole file can be replayed
+/// into the same engine.
+pub struct RecordingCollector<E> {
+    collector: Box<dyn Collector<E>>,
+    recorder: EventRecorder,
+}
+
+impl<E> RecordingCollector<E> {
+    pub fn new(collector: Box<dyn Collector<E>>, recorder: EventRecorder) -> Self {
+        Self {
+            collector,
+            recorder,
+        }
+    }
+}
+
+#[async_trait]
+impl<E> Collector<E> for RecordingCollector<E>
+where
+    E: Serialize + Send + Sync + 'static,
+{
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, E>> {
+        let stream = self.collector.get_event_stream().await?;
+        let recorder = self.recorder.clone();
+        let stream = stream.map(move |event| {
+            if let Err(e) = recorder.record(&event) {
+                error!("error recording event: {}", e);
+            }
+            event
+        });
+        Ok(Box::pin(stream))
+    }
+}
@@ -2,3 +2,9 @@
 
 /// This module implements state overriding middleware.
 pub mod state_override_middleware;
+
+/// This module implements recording of collector events to a file.
+pub mod event_recorder;
+
+/// This module implements saving and restoring strategy state snapshots.
+pub mod snapshot;
@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{info, warn};
+
+use crate::types::{ActionOutcome, Snapshot, StateSnapshot, Strategy, StrategyFault};
+
+/// Saves and loads [StateSnapshot](StateSnapshot)s as JSON files.
+#[derive(Debug, Clone)]
+pub struct SnapshotStore {
+    path: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Loads the snapshot, or returns `None` if none has been saved yet.
+    pub fn load<S: DeserializeOwned>(&self) -> Result<Option<StateSnapshot<S>>> {
+        let contents = match std::fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let snapshot = serde_json::from_slice(&contents)
+            .with_context(|| format!("error parsing snapshot {}", self.path.display()))?;
+        Ok(Some(snapshot))
+    }
+
+    /// Saves the snapshot, replacing the previous one. The snapshot is written
+    /// to a temporary file first, so that a crash never leaves a partially
+    /// written snapshot behind.
+    pub fn save<S: Serialize>(&self, snapshot: &StateSnapshot<S>) -> Result<()> {
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec(snapshot)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// SnapshotStrategy is a wrapper around a [Strategy](Strategy) implementing
+/// [Snapshot](Snapshot), which restores the last snapshot of the strategy
+/// before syncing its state, and saves a new one after every sync and on
+/// shutdown.
+pub struct SnapshotStrategy<S> {
+    strategy: S,
+    store: SnapshotStore,
+}
+
+impl<S: Snapshot> SnapshotStrategy<S> {
+    pub fn new(strategy: S, store: SnapshotStore) -> Self {
+        Self { strategy, store }
+    }
+
+    /// Saves a snapshot of the strategy, if it has any state to save.
+    fn save(&self) {
+        let Some(snapshot) = self.strategy.snapshot() else {
+            return;
+        };
+        match self.store.save(&snapshot) {
+            Ok(()) => info!("saved snapshot at block {}", snapshot.block_number),
+            Err(e) => warn!("error saving snapshot: {}", e),
+        }
+    }
+}
+
+#[async_trait]
+impl<E, A, S> Strategy<E, A> for SnapshotStrategy<S>
+where
+    E: Send + 'static,
+    A: Send + 'static,
+    S: Strategy<E, A> + Snapshot,
+{
+    /// Restores the last snapshot, if any, then syncs the rest of the state.
+    /// A snapshot that cannot be loaded is ignored, so the state is synced
+    /// from scratch.
+    async fn sync_state(&mut self) -> Result<()> {
+        match self.store.load() {
+            Ok(Some(snapshot)) => {
+                info!("restoring snapshot at block {}", snapshot.block_number);
+                self.strategy.restore(snapshot);
+            }
+            Ok(None) => info!("no snapshot found, syncing from scratch"),
+            Err(e) => warn!("error loading snapshot, syncing from scratch: {}", e),
+        }
+        self.strategy.sync_state().await?;
+        self.save();
+        Ok(())
+    }
+
+    async fn on_start(&mut self) -> Vec<A> {
+        self.strategy.on_start().await
+    }
+
+    async fn process_event(&mut self, event: E) -> Vec<A> {
+        self.strategy.process_event(event).await
+    }
+
+    async fn on_execution_outcome(&mut self, outcome: ActionOutcome) -> Vec<A> {
+        self.strategy.on_execution_outcome(outcome).await
+    }
+
+    fn take_fault(&mut self) -> Option<StrategyFault> {
+        self.strategy.take_fault()
+    }
+
+    async fn resync(&mut self) -> Result<()> {
+        self.strategy.resync().await?;
+        self.save();
+        Ok(())
+    }
+
+    async fn on_shutdown(&mut self) -> Vec<A> {
+        let actions = self.strategy.on_shutdown().await;
+        self.save();
+        actions
+    }
+}
@@ -1,16 +1,58 @@
+use anyhow::Result;
 use artemis_core::{
-    collectors::{block_collector::BlockCollector, mempool_collector::MempoolCollector},
-    executors::mempool_executor::{MempoolExecutor, SubmitTxToMempool},
-    types::{Collector, Executor},
+    collectors::{
+        block_collector::{BlockCollector, BlockEvent, BlockMode, NewBlock, Reorg, ReorgDetector},
+        interval_collector::IntervalCollector,
+        log_collector::LogCollector,
+        mempool_collector::{CallDecoder, MempoolCollector, MempoolFilter},
+        mevshare_collector::{HintKind, MevShareHint, SwapHint, SwapProtocol},
+        opensea_order_collector::{OpenseaEvent, OpenseaOrderCollector},
+        oracle_collector::{OracleCollector, PriceFeed, PriceUpdate},
+        replay_collector::ReplayCollector,
+        txpool_collector::{TxpoolApi, TxpoolCollector, TxpoolEvent},
+    },
+    engine::{
+        CollectorOptions, Engine, ExecutorOptions, OverflowPolicy, RestartPolicy, StrategyOptions,
+    },
+    executors::{
+        fan_out_executor::{FanOutExecutor, FanOutPolicy},
+        mempool_executor::{MempoolExecutor, SubmitTxToMempool},
+    },
+    types::{
+        ActionOutcome, Collector, CollectorExt, CollectorStream, ExecutionOutcome, Executor,
+        FaultAction, OutcomeReporter, Snapshot, StateSnapshot, Strategy, StrategyFault,
+        StrategyMap,
+    },
+    utilities::{
+        event_recorder::{EventRecorder, RecordingCollector},
+        snapshot::{SnapshotStore, SnapshotStrategy},
+    },
 };
+use async_trait::async_trait;
 use ethers::providers::StreamExt;
 use ethers::{
-    providers::{Middleware, Provider, Ws},
-    types::{BlockNumber, TransactionRequest, U256},
-    utils::{Anvil, AnvilInstance},
+    abi::{parse_abi, Token},
+    providers::{JsonRpcError, Middleware, MockProvider, MockResponse, Provider, Ws},
+    types::{
+        Address, Block, BlockNumber, Filter, Log, Transaction, TransactionReceipt,
+        TransactionRequest, H256, I256, U256, U64,
+    },
+    utils::{keccak256, Anvil, AnvilInstance},
+};
+use mev_share::sse::{Event, EventTransaction, EventTransactionLog, FunctionSelector};
+use opensea_stream::{Collection, Network};
+use serde_json::json;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::{Barrier, Notify},
+    time::{sleep, timeout},
 };
-use std::{sync::Arc, time::Duration};
-use tokio::time::sleep;
 
 /// Spawns Anvil and instantiates an Http provider.
 pub async fn spawn_anvil() -> (Provider<Ws>, AnvilInstance) {
@@ -22,6 +64,311 @@ pub async fn spawn_anvil() -> (Provider<Ws>, AnvilInstance) {
     (provider, anvil)
 }
 
+/// Values recorded by a test component, which tests can wait on instead of
+/// sleeping for a fixed time.
+#[derive(Clone)]
+struct Recorded<T> {
+    values: Arc<Mutex<Vec<T>>>,
+    notify: Arc<Notify>,
+}
+
+impl<T> Default for Recorded<T> {
+    fn default() -> Self {
+        Self {
+            values: Arc::new(Mutex::new(vec![])),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+}
+
+impl<T: Clone> Recorded<T> {
+    fn push(&self, value: T) {
+        self.values.lock().unwrap().push(value);
+        self.notify.notify_waiters();
+    }
+
+    fn get(&self) -> Vec<T> {
+        self.values.lock().unwrap().clone()
+    }
+
+    /// Waits until at least `count` values were recorded, failing the test if
+    /// they are not within a few seconds.
+    async fn wait_for(&self, count: usize) {
+        self.wait_until(|values| values.len() >= count).await
+    }
+
+    /// Waits until the recorded values match `condition`, failing the test if
+    /// they do not within a few seconds.
+    async fn wait_until(&self, condition: impl Fn(&[T]) -> bool) {
+        let wait = async {
+            loop {
+                // Register for the next notification before checking, so that
+                // values pushed in between are not missed.
+                let notified = self.notify.notified();
+                if condition(&self.values.lock().unwrap()) {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        timeout(Duration::from_secs(5), wait)
+            .await
+            .expect("timed out waiting for recorded values");
+    }
+}
+
+/// A collector that emits a fixed set of events, then stays open without
+/// emitting anything else.
+struct MockCollector {
+    events: Vec<u64>,
+}
+
+#[async_trait]
+impl Collector<u64> for MockCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        let stream = tokio_stream::iter(self.events.clone()).chain(tokio_stream::pending());
+        Ok(Box::pin(stream))
+    }
+}
+
+/// A collector whose first event stream ends after a single event, and whose
+/// later streams end straight away.
+#[derive(Default)]
+struct FlakyCollector {
+    connections: AtomicU64,
+}
+
+#[async_trait]
+impl Collector<u64> for FlakyCollector {
+    async fn get_event_stream(&self) -> Result<CollectorStream<'_, u64>> {
+        let events = match self.connections.fetch_add(1, Ordering::SeqCst) {
+            0 => vec![1],
+            _ => vec![],
+        };
+        Ok(Box::pin(tokio_stream::iter(events)))
+    }
+}
+
+/// A strategy that doubles every event it receives.
+struct MockStrategy;
+
+#[async_trait]
+impl Strategy<u64, u64> for MockStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        vec![event * 2]
+    }
+}
+
+/// An executor that records every action it executes.
+#[derive(Clone, Default)]
+struct MockExecutor {
+    executed: Recorded<u64>,
+}
+
+#[async_trait]
+impl Executor<u64> for MockExecutor {
+    async fn execute(&self, action: u64) -> Result<()> {
+        // Simulate a slow submission so actions are still pending at shutdown.
+        sleep(Duration::from_millis(10)).await;
+        self.executed.push(action);
+        Ok(())
+    }
+}
+
+/// A strategy that takes a while to sync, then doubles every event it receives.
+struct SlowSyncStrategy {
+    sync_delay: Duration,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for SlowSyncStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        sleep(self.sync_delay).await;
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        vec![event * 2]
+    }
+}
+
+/// A strategy that can only finish syncing once all the strategies sharing its
+/// barrier are syncing too.
+struct BarrierSyncStrategy {
+    barrier: Arc<Barrier>,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for BarrierSyncStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        timeout(Duration::from_secs(5), self.barrier.wait()).await?;
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        vec![event * 2]
+    }
+}
+
+/// A strategy that forwards every event as an action, records the outcomes of
+/// its actions and retries failed actions once.
+#[derive(Clone, Default)]
+struct RetryingStrategy {
+    outcomes: Recorded<ActionOutcome>,
+    /// The actions returned so far, indexed by id, and whether they are retries.
+    actions: Vec<(u64, bool)>,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for RetryingStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        self.actions.push((event, false));
+        vec![event]
+    }
+
+    async fn on_execution_outcome(&mut self, outcome: ActionOutcome) -> Vec<u64> {
+        let (action, retried) = self.actions[outcome.action_id as usize];
+        let retry = matches!(outcome.outcome, ExecutionOutcome::Failed { .. }) && !retried;
+        self.outcomes.push(outcome);
+        if retry {
+            self.actions.push((action, true));
+            vec![action]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// A strategy that forwards every event as an action, except for `2`: it
+/// panics on it if no fault action is set, and reports a fault otherwise.
+#[derive(Clone, Default)]
+struct FaultyStrategy {
+    action: Option<FaultAction>,
+    syncs: Arc<AtomicU64>,
+    fault: Option<StrategyFault>,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for FaultyStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        self.syncs.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        if event != 2 {
+            return vec![event];
+        }
+        match self.action {
+            Some(action) => self.fault = Some(StrategyFault::new(action, "bad event")),
+            None => panic!("bad event"),
+        }
+        vec![]
+    }
+
+    fn take_fault(&mut self) -> Option<StrategyFault> {
+        self.fault.take()
+    }
+}
+
+/// A strategy that forwards every event as an action, emits `100` on startup
+/// and `200` on shutdown, and records every time it was resynced.
+#[derive(Clone, Default)]
+struct LifecycleStrategy {
+    resyncs: Recorded<()>,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for LifecycleStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_start(&mut self) -> Vec<u64> {
+        vec![100]
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        vec![event]
+    }
+
+    async fn resync(&mut self) -> Result<()> {
+        self.resyncs.push(());
+        Ok(())
+    }
+
+    async fn on_shutdown(&mut self) -> Vec<u64> {
+        vec![200]
+    }
+}
+
+/// A strategy that sums the events it receives, treating every event as the
+/// number of the block it was emitted in, and records the events it processed
+/// and the block it synced from.
+#[derive(Clone, Default)]
+struct SummingStrategy {
+    total: u64,
+    block_number: Option<u64>,
+    processed: Recorded<u64>,
+    synced_from: Arc<Mutex<Option<u64>>>,
+}
+
+#[async_trait]
+impl Strategy<u64, u64> for SummingStrategy {
+    async fn sync_state(&mut self) -> Result<()> {
+        *self.synced_from.lock().unwrap() = self.block_number;
+        Ok(())
+    }
+
+    async fn process_event(&mut self, event: u64) -> Vec<u64> {
+        self.total += event;
+        self.block_number = Some(event);
+        self.processed.push(event);
+        vec![]
+    }
+}
+
+impl Snapshot for SummingStrategy {
+    type State = u64;
+
+    fn snapshot(&self) -> Option<StateSnapshot<u64>> {
+        Some(StateSnapshot {
+            block_number: self.block_number?,
+            state: self.total,
+        })
+    }
+
+    fn restore(&mut self, snapshot: StateSnapshot<u64>) {
+        self.total = snapshot.state;
+        self.block_number = Some(snapshot.block_number);
+    }
+}
+
+/// An executor that fails to execute odd actions.
+#[derive(Clone, Default)]
+struct OddFailingExecutor {
+    executed: Recorded<u64>,
+}
+
+#[async_trait]
+impl Executor<u64> for OddFailingExecutor {
+    async fn execute(&self, action: u64) -> Result<()> {
+        if action % 2 == 1 {
+            anyhow::bail!("odd action {}", action);
+        }
+        self.executed.push(action);
+        Ok(())
+    }
+}
+
 /// Test that block collector correctly emits blocks.
 #[tokio::test]
 async fn test_block_collector_sends_blocks() {
@@ -36,6 +383,141 @@ async fn test_block_collector_sends_blocks() {
         .unwrap()
         .unwrap();
     assert_eq!(block_a.hash, block_b.hash.unwrap());
+    assert_eq!(block_a.parent_hash, block_b.parent_hash);
+    assert_eq!(block_a.base_fee_per_gas, block_b.base_fee_per_gas);
+}
+
+/// Test that a block collector includes the transactions and receipts of blocks
+/// depending on its mode, and predicts the base fee of the next block.
+#[tokio::test]
+async fn test_block_collector_modes() {
+    async fn collect(provider: Arc<Provider<MockProvider>>, mode: BlockMode) -> NewBlock {
+        let collector = BlockCollector::new(provider)
+            .with_mode(mode)
+            .with_polling(Duration::from_millis(10));
+        let mut stream = collector.get_event_stream().await.unwrap();
+        stream.next().await.unwrap()
+    }
+
+    // Mock responses are returned in reverse order.
+    let (provider, mock) = Provider::mocked();
+    let provider = Arc::new(provider);
+    let tx = Transaction {
+        hash: H256::repeat_byte(3),
+        ..Default::default()
+    };
+    let receipt = TransactionReceipt {
+        transaction_hash: tx.hash,
+        ..Default::default()
+    };
+    let block = Block::<Transaction> {
+        hash: Some(H256::repeat_byte(2)),
+        number: Some(U64::from(2)),
+        gas_limit: U256::from(30_000_000),
+        base_fee_per_gas: Some(U256::from(1000)),
+        transactions: vec![tx.clone()],
+        ..Default::default()
+    };
+    let watch = |block_hash: H256| {
+        mock.push::<Vec<H256>, _>(vec![block_hash]).unwrap();
+        mock.push::<U256, _>(U256::from(1)).unwrap();
+    };
+
+    // The base fee goes up by an eighth at most when blocks are full, and down
+    // by an eighth at most when they are empty.
+    for (gas_used, next_base_fee) in [(30_000_000, 1125), (15_000_000, 1000), (0, 875)] {
+        let header = Block::<H256> {
+            gas_used: U256::from(gas_used),
+            transactions: vec![tx.hash],
+            ..block.clone().into()
+        };
+        mock.push::<Block<H256>, _>(header).unwrap();
+        watch(block.hash.unwrap());
+        let new_block = collect(provider.clone(), BlockMode::Header).await;
+        assert_eq!(new_block.base_fee_per_gas, Some(U256::from(1000)));
+        assert_eq!(
+            new_block.next_base_fee_per_gas,
+            Some(U256::from(next_base_fee))
+        );
+        assert_eq!(new_block.transactions, None);
+        assert_eq!(new_block.receipts, None);
+    }
+
+    mock.push::<Block<Transaction>, _>(block.clone()).unwrap();
+    watch(block.hash.unwrap());
+    let new_block = collect(provider.clone(), BlockMode::Transactions).await;
+    assert_eq!(new_block.transactions, Some(vec![tx.clone()]));
+    assert_eq!(new_block.receipts, None);
+
+    mock.push::<Vec<TransactionReceipt>, _>(vec![receipt.clone()])
+        .unwrap();
+    mock.push::<Block<Transaction>, _>(block.clone()).unwrap();
+    watch(block.hash.unwrap());
+    let new_block = collect(provider.clone(), BlockMode::Receipts).await;
+    assert_eq!(new_block.transactions, Some(vec![tx]));
+    assert_eq!(new_block.receipts, Some(vec![receipt]));
+
+    // A pre-London block has no base fee to predict from.
+    let header = Block::<H256> {
+        base_fee_per_gas: None,
+        ..block.clone().into()
+    };
+    mock.push::<Block<H256>, _>(header).unwrap();
+    watch(block.hash.unwrap());
+    let new_block = collect(provider, BlockMode::Header).await;
+    assert_eq!(new_block.next_base_fee_per_gas, None);
+}
+
+/// Test that the reorg detector fetches the blocks of a new fork back to the
+/// common ancestor, and reports the blocks it orphaned.
+#[tokio::test]
+async fn test_reorg_detector_detects_reorgs() {
+    let block = |number: u64, hash: u8, parent: u8| NewBlock {
+        hash: H256::repeat_byte(hash),
+        number: number.into(),
+        parent_hash: H256::repeat_byte(parent),
+        ..Default::default()
+    };
+    let fork = vec![block(2, 0xb2, 0xa1), block(3, 0xb3, 0xb2)];
+    let fetch_block = |hash: H256| {
+        let fork = fork.clone();
+        async move {
+            fork.into_iter()
+                .find(|block| block.hash == hash)
+                .ok_or_else(|| anyhow::anyhow!("unknown block"))
+        }
+    };
+
+    let mut detector = ReorgDetector::new(8);
+    for new_block in [
+        block(1, 0xa1, 0xa0),
+        block(2, 0xa2, 0xa1),
+        block(3, 0xa3, 0xa2),
+    ] {
+        let events = detector
+            .apply(new_block.clone(), fetch_block)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![BlockEvent::NewBlock(new_block)]);
+    }
+    let events = detector
+        .apply(block(3, 0xa3, 0xa2), fetch_block)
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+
+    let events = detector
+        .apply(block(4, 0xb4, 0xb3), fetch_block)
+        .await
+        .unwrap();
+    let new = vec![fork[0].clone(), fork[1].clone(), block(4, 0xb4, 0xb3)];
+    let mut expected = vec![BlockEvent::Reorg(Reorg {
+        depth: 2,
+        old: vec![block(2, 0xa2, 0xa1), block(3, 0xa3, 0xa2)],
+        new: new.clone(),
+    })];
+    expected.extend(new.into_iter().map(BlockEvent::NewBlock));
+    assert_eq!(events, expected);
 }
 
 /// Test that mempool collector correctly emits blocks.
@@ -85,3 +567,1126 @@ async fn test_mempool_executor_sends_tx_simple() {
     let tx = provider.get_transaction_count(account, None).await.unwrap();
     assert_eq!(tx, 1.into());
 }
+
+/// Test that shutting down the engine drains pending events and actions.
+#[tokio::test]
+async fn test_engine_graceful_shutdown() {
+    let executor = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    engine.add_strategy(Box::new(MockStrategy));
+    engine.add_executor(Box::new(executor.clone()));
+
+    // Shut down while the later actions are still pending.
+    let handle = engine.run().await.unwrap();
+    executor.executed.wait_for(1).await;
+    let summary = handle.shutdown().await;
+
+    assert_eq!(executor.executed.get(), vec![2, 4, 6]);
+    assert_eq!(summary.events_collected, 3);
+    assert_eq!(summary.actions_executed, 3);
+    assert_eq!(summary.tasks_failed, 0);
+}
+
+/// Test that a supervised collector is restarted until it runs out of retries.
+#[tokio::test]
+async fn test_engine_restarts_collector() {
+    let executor = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    let policy = RestartPolicy::exponential(Duration::from_millis(1), Duration::from_millis(5))
+        .with_max_retries(2);
+    engine.add_supervised_collector(Box::new(FlakyCollector::default()), policy);
+    engine.add_strategy(Box::new(MockStrategy));
+    engine.add_executor(Box::new(executor.clone()));
+
+    let summary = engine.run().await.unwrap().wait().await;
+
+    assert_eq!(executor.executed.get(), vec![2]);
+    assert_eq!(summary.events_collected, 1);
+    assert_eq!(summary.collector_restarts, 2);
+}
+
+/// Test that executors apply their overflow policy when they fall behind.
+#[tokio::test]
+async fn test_engine_overflow_policies() {
+    async fn run_with_policy(policy: OverflowPolicy) -> (Vec<u64>, u64) {
+        let executor = MockExecutor::default();
+        let mut engine: Engine<u64, u64> = Engine::default().with_action_channel_capacity(1);
+        engine.add_collector(Box::new(MockCollector {
+            events: vec![1, 2, 3, 4, 5],
+        }));
+        engine.add_strategy(Box::new(MockStrategy));
+        engine.add_executor_with_options(
+            Box::new(executor.clone()),
+            ExecutorOptions::default().with_overflow_policy(policy),
+        );
+
+        // Every policy ends up executing the last action.
+        let handle = engine.run().await.unwrap();
+        executor
+            .executed
+            .wait_until(|executed| executed.contains(&10))
+            .await;
+        let summary = handle.shutdown().await;
+        let executed = executor.executed.get();
+        (executed, summary.actions_dropped)
+    }
+
+    // A blocking executor sees every action.
+    let (executed, dropped) = run_with_policy(OverflowPolicy::Block).await;
+    assert_eq!(executed, vec![2, 4, 6, 8, 10]);
+    assert_eq!(dropped, 0);
+
+    // Otherwise the executor skips ahead to the most recent actions.
+    let (executed, dropped) = run_with_policy(OverflowPolicy::DropOldest).await;
+    assert!(dropped > 0);
+    assert_eq!(executed.len() as u64 + dropped, 5);
+    assert_eq!(executed.last(), Some(&10));
+}
+
+/// Test that execution outcomes are reported back to the strategy which
+/// produced the action, and that its follow-up actions are executed.
+#[tokio::test]
+async fn test_engine_reports_execution_outcomes() {
+    let strategy = RetryingStrategy::default();
+    let executor = OddFailingExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector { events: vec![1, 2] }));
+    engine.add_strategy(Box::new(strategy.clone()));
+    engine.add_executor(Box::new(executor.clone()));
+
+    let handle = engine.run().await.unwrap();
+    strategy.outcomes.wait_for(3).await;
+    let summary = handle.shutdown().await;
+
+    assert_eq!(executor.executed.get(), vec![2]);
+
+    // The failed action is retried once, under a new id.
+    let mut outcomes = strategy.outcomes.get();
+    outcomes.sort_by_key(|outcome| outcome.action_id);
+    let failed = ExecutionOutcome::Failed {
+        reason: "odd action 1".to_string(),
+    };
+    assert_eq!(
+        outcomes,
+        vec![
+            ActionOutcome {
+                action_id: 0,
+                executor: Some("executor-0".to_string()),
+                outcome: failed.clone()
+            },
+            ActionOutcome {
+                action_id: 1,
+                executor: Some("executor-0".to_string()),
+                outcome: ExecutionOutcome::Submitted { tx_hashes: vec![] }
+            },
+            ActionOutcome {
+                action_id: 2,
+                executor: Some("executor-0".to_string()),
+                outcome: failed
+            },
+        ]
+    );
+    assert_eq!(summary.actions_emitted, 3);
+    assert_eq!(summary.actions_failed, 2);
+}
+
+/// Test that actions are only delivered to the executors which accept them,
+/// and that strategies can target executors by name, or by label if unnamed.
+#[tokio::test]
+async fn test_engine_routes_actions() {
+    let small = MockExecutor::default();
+    let even = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    // Emits 2, 4 and 6 to every executor.
+    engine.add_strategy(Box::new(MockStrategy));
+    // Emits 1, 2 and 3 to the "small" executor only.
+    engine.add_strategy_with_options(
+        Box::new(RetryingStrategy::default()),
+        StrategyOptions::default().with_executor("small"),
+    );
+    // Emits 1, 2 and 3 to the unnamed executor only.
+    engine.add_strategy_with_options(
+        Box::new(RetryingStrategy::default()),
+        StrategyOptions::default().with_executor("executor-1"),
+    );
+    engine.add_executor_with_options(
+        Box::new(small.clone()),
+        ExecutorOptions::default()
+            .with_name("small")
+            .with_filter(|action| *action < 5),
+    );
+    engine.add_executor_with_options(
+        Box::new(even.clone()),
+        ExecutorOptions::default().with_filter(|action| action % 2 == 0),
+    );
+
+    let handle = engine.run().await.unwrap();
+    small.executed.wait_for(5).await;
+    even.executed.wait_for(4).await;
+    let summary = handle.shutdown().await;
+
+    let mut small = small.executed.get();
+    small.sort();
+    assert_eq!(small, vec![1, 2, 2, 3, 4]);
+    let mut even = even.executed.get();
+    even.sort();
+    assert_eq!(even, vec![2, 2, 4, 6]);
+    assert_eq!(summary.actions_emitted, 9);
+    // The odd actions sent to the unnamed executor are filtered out.
+    assert_eq!(summary.actions_unrouted, 2);
+}
+
+/// Test that targeting an executor which does not exist fails to start the
+/// engine.
+#[tokio::test]
+async fn test_engine_rejects_unknown_executor() {
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_strategy_with_options(
+        Box::new(MockStrategy),
+        StrategyOptions::default().with_executor("missing"),
+    );
+    engine.add_executor(Box::new(MockExecutor::default()));
+    assert!(engine.run().await.is_err());
+}
+
+/// Test that strategies sync concurrently, and that the events emitted while
+/// they sync are replayed to them afterwards.
+#[tokio::test]
+async fn test_engine_syncs_strategies_concurrently() {
+    let executor = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    let barrier = Arc::new(Barrier::new(2));
+    for _ in 0..2 {
+        engine.add_strategy(Box::new(BarrierSyncStrategy {
+            barrier: barrier.clone(),
+        }));
+    }
+    engine.add_executor(Box::new(executor.clone()));
+
+    let handle = engine.run().await.unwrap();
+    executor.executed.wait_for(6).await;
+    let summary = handle.shutdown().await;
+
+    let mut executed = executor.executed.get();
+    executed.sort();
+    assert_eq!(executed, vec![2, 2, 4, 4, 6, 6]);
+    assert_eq!(summary.events_processed, 6);
+}
+
+/// Test that the engine fails to start if a strategy takes too long to sync.
+#[tokio::test]
+async fn test_engine_sync_timeout() {
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_strategy(Box::new(MockStrategy));
+    engine.add_strategy_with_options(
+        Box::new(SlowSyncStrategy {
+            sync_delay: Duration::from_secs(10),
+        }),
+        StrategyOptions::default().with_sync_timeout(Duration::from_millis(10)),
+    );
+    engine.add_executor(Box::new(MockExecutor::default()));
+    assert!(engine.run().await.is_err());
+}
+
+/// Test that a panicking strategy is disabled without affecting the other
+/// strategies.
+#[tokio::test]
+async fn test_engine_isolates_strategy_panics() {
+    let executor = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    engine.add_strategy(Box::new(MockStrategy));
+    engine.add_strategy(Box::new(FaultyStrategy::default()));
+    engine.add_executor(Box::new(executor.clone()));
+
+    let handle = engine.run().await.unwrap();
+    executor.executed.wait_for(4).await;
+    let summary = handle.shutdown().await;
+
+    let mut executed = executor.executed.get();
+    executed.sort();
+    assert_eq!(executed, vec![1, 2, 4, 6]);
+    assert_eq!(summary.strategy_faults, 1);
+    assert_eq!(summary.tasks_failed, 0);
+}
+
+/// Test that a strategy reporting a resync fault is synced again, then keeps
+/// processing events.
+#[tokio::test]
+async fn test_engine_resyncs_faulty_strategy() {
+    let executor = MockExecutor::default();
+    let strategy = FaultyStrategy {
+        action: Some(FaultAction::Resync),
+        ..Default::default()
+    };
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    engine.add_strategy(Box::new(strategy.clone()));
+    engine.add_executor(Box::new(executor.clone()));
+
+    let handle = engine.run().await.unwrap();
+    executor.executed.wait_for(2).await;
+    let summary = handle.shutdown().await;
+
+    assert_eq!(executor.executed.get(), vec![1, 3]);
+    assert_eq!(strategy.syncs.load(Ordering::SeqCst), 2);
+    assert_eq!(summary.strategy_faults, 1);
+}
+
+/// Test that a strategy reporting a stop fault shuts the engine down.
+#[tokio::test]
+async fn test_engine_stops_on_strategy_fault() {
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    engine.add_strategy(Box::new(FaultyStrategy {
+        action: Some(FaultAction::Stop),
+        ..Default::default()
+    }));
+    engine.add_executor(Box::new(MockExecutor::default()));
+
+    let handle = engine.run().await.unwrap();
+    let summary = tokio::time::timeout(Duration::from_secs(5), handle.wait())
+        .await
+        .unwrap();
+    assert_eq!(summary.strategy_faults, 1);
+}
+
+/// Test that the engine calls the lifecycle hooks of strategies, and executes
+/// their shutdown actions before stopping.
+#[tokio::test]
+async fn test_engine_strategy_lifecycle() {
+    let executor = MockExecutor::default();
+    let strategy = LifecycleStrategy::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector { events: vec![1] }));
+    engine.add_strategy_with_options(
+        Box::new(strategy.clone()),
+        StrategyOptions::default().with_resync_interval(Duration::from_millis(20)),
+    );
+    engine.add_executor(Box::new(executor.clone()));
+
+    let handle = engine.run().await.unwrap();
+    executor.executed.wait_for(2).await;
+    strategy.resyncs.wait_for(3).await;
+    let summary = handle.shutdown().await;
+
+    assert_eq!(executor.executed.get(), vec![100, 1, 200]);
+    assert!(strategy.resyncs.get().len() >= 3);
+    assert_eq!(summary.strategy_faults, 0);
+}
+
+/// Test that a mapped strategy only receives the events it accepts, and that
+/// its actions are mapped.
+#[tokio::test]
+async fn test_engine_maps_strategy() {
+    let executor = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    engine.add_strategy(Box::new(StrategyMap::new(
+        Box::new(MockStrategy),
+        |event: u64| (event % 2 == 1).then_some(event),
+        |action: u64| action + 1,
+    )));
+    engine.add_executor(Box::new(executor.clone()));
+
+    let handle = engine.run().await.unwrap();
+    executor.executed.wait_for(2).await;
+    handle.shutdown().await;
+
+    assert_eq!(executor.executed.get(), vec![3, 7]);
+}
+
+/// Test that the engine records its pipeline metrics and serves them over HTTP.
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn test_engine_metrics() {
+    use artemis_core::engine::EngineMetrics;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let addr: std::net::SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let executor = MockExecutor::default();
+    let metrics = Arc::new(EngineMetrics::new().unwrap());
+    let mut engine: Engine<u64, u64> = Engine::default()
+        .with_metrics(metrics.clone())
+        .with_metrics_endpoint(addr);
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    engine.add_strategy(Box::new(MockStrategy));
+    engine.add_executor_with_options(
+        Box::new(executor.clone()),
+        ExecutorOptions::default().with_name("mock"),
+    );
+
+    let handle = engine.run().await.unwrap();
+    let addr = handle.metrics_addr().unwrap();
+    let scrape = || async {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    };
+
+    // Scrape until the last action is counted, which is recorded after the
+    // events and actions that led to it.
+    executor.executed.wait_for(3).await;
+    let executed = "artemis_executor_actions_total{component=\"mock\",result=\"success\"} 3";
+    let response = timeout(Duration::from_secs(5), async {
+        loop {
+            let response = scrape().await;
+            if response.contains(executed) {
+                return response;
+            }
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("timed out waiting for the executor metrics");
+    handle.shutdown().await;
+
+    assert!(response.contains("artemis_collector_events_total{component=\"collector-0\"} 3"));
+    assert!(response.contains("artemis_strategy_actions_total{component=\"strategy-0\"} 3"));
+
+    // An engine which fails to start does not serve its metrics.
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut engine: Engine<u64, u64> = Engine::default()
+        .with_metrics(Arc::new(EngineMetrics::new().unwrap()))
+        .with_metrics_endpoint(addr);
+    engine.add_strategy_with_options(
+        Box::new(MockStrategy),
+        StrategyOptions::default().with_executor("missing"),
+    );
+    assert!(engine.run().await.is_err());
+    assert!(std::net::TcpListener::bind(addr).is_ok());
+}
+
+/// Test that recorded events can be replayed through the engine.
+#[tokio::test]
+async fn test_engine_records_and_replays_events() {
+    let path = std::env::temp_dir().join(format!("artemis-events-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    // Record the events of a live run.
+    let recorder = EventRecorder::create(&path).unwrap();
+    let executor = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(RecordingCollector::new(
+        Box::new(MockCollector {
+            events: vec![1, 2, 3],
+        }),
+        recorder.clone(),
+    )));
+    engine.add_strategy(Box::new(MockStrategy));
+    engine.add_executor(Box::new(executor.clone()));
+    let handle = engine.run().await.unwrap();
+    executor.executed.wait_for(3).await;
+    handle.shutdown().await;
+    recorder.flush().await.unwrap();
+
+    // Replay them, after which the engine stops on its own.
+    let executor = MockExecutor::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(ReplayCollector::new(&path)));
+    engine.add_strategy(Box::new(MockStrategy));
+    engine.add_executor(Box::new(executor.clone()));
+    let summary = engine.run().await.unwrap().wait().await;
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(executor.executed.get(), vec![2, 4, 6]);
+    assert_eq!(summary.events_collected, 3);
+}
+
+/// Test that a snapshot strategy saves its state on shutdown, and restores it
+/// before syncing on the next start.
+#[tokio::test]
+async fn test_snapshot_strategy_restores_state() {
+    let path = std::env::temp_dir().join(format!("artemis-snapshot-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let strategy = SummingStrategy::default();
+    let mut engine: Engine<u64, u64> = Engine::default();
+    engine.add_collector(Box::new(MockCollector {
+        events: vec![1, 2, 3],
+    }));
+    engine.add_strategy(Box::new(SnapshotStrategy::new(
+        strategy.clone(),
+        SnapshotStore::new(&path),
+    )));
+    engine.add_executor(Box::new(MockExecutor::default()));
+    let handle = engine.run().await.unwrap();
+    strategy.processed.wait_for(3).await;
+    handle.shutdown().await;
+
+    let strategy = SummingStrategy::default();
+    let mut restarted = SnapshotStrategy::new(strategy.clone(), SnapshotStore::new(&path));
+    Strategy::<u64, u64>::sync_state(&mut restarted)
+        .await
+        .unwrap();
+    let snapshot: StateSnapshot<u64> = SnapshotStore::new(&path).load().unwrap().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(*strategy.synced_from.lock().unwrap(), Some(3));
+    assert_eq!(
+        snapshot,
+        StateSnapshot {
+            block_number: 3,
+            state: 6
+        }
+    );
+}
+
+/// Test that merged collectors are deduplicated and filtered.
+#[tokio::test]
+async fn test_collector_ext_merges_and_dedups() {
+    let collector = MockCollector {
+        events: vec![1, 2, 3, 4],
+    }
+    .merge(MockCollector {
+        events: vec![3, 4, 5, 6],
+    })
+    .dedup_by_key(|event| *event, Duration::from_secs(60))
+    .filter_map(|event| (event % 2 == 0).then_some(event * 10));
+    let stream = collector.get_event_stream().await.unwrap();
+
+    let mut events: Vec<u64> = stream.take(3).collect().await;
+    events.sort();
+    assert_eq!(events, vec![20, 40, 60]);
+}
+
+/// Test that batched, throttled and debounced collectors group or drop events
+/// received in quick succession.
+#[tokio::test]
+async fn test_collector_ext_batches_and_rate_limits() {
+    let collector = MockCollector {
+        events: vec![1, 2, 3, 4, 5],
+    }
+    .batch(2, Duration::from_millis(20));
+    let stream = collector.get_event_stream().await.unwrap();
+    let batches: Vec<Vec<u64>> = stream.take(3).collect().await;
+    assert_eq!(batches, vec![vec![1, 2], vec![3, 4], vec![5]]);
+
+    let collector = MockCollector {
+        events: vec![1, 2, 3],
+    }
+    .throttle(Duration::from_secs(60));
+    let mut stream = collector.get_event_stream().await.unwrap();
+    assert_eq!(stream.next().await, Some(1));
+    assert!(
+        tokio::time::timeout(Duration::from_millis(20), stream.next())
+            .await
+            .is_err()
+    );
+
+    let collector = MockCollector {
+        events: vec![1, 2, 3],
+    }
+    .debounce(Duration::from_millis(20));
+    let mut stream = collector.get_event_stream().await.unwrap();
+    assert_eq!(stream.next().await, Some(3));
+}
+
+/// Test that a fan-out executor sends actions to every executor, applies its
+/// policy to their results and reports an outcome for each of them.
+#[tokio::test]
+async fn test_fan_out_executor_policies() {
+    let (slow, fast) = (MockExecutor::default(), OddFailingExecutor::default());
+    let fan_out = |policy| {
+        FanOutExecutor::new(policy)
+            .with_executor("slow", Box::new(slow.clone()))
+            .with_executor("fast", Box::new(fast.clone()))
+    };
+
+    assert!(fan_out(FanOutPolicy::AllMustSucceed)
+        .execute(1)
+        .await
+        .is_err());
+    assert!(fan_out(FanOutPolicy::AllMustSucceed)
+        .execute(2)
+        .await
+        .is_ok());
+    assert!(fan_out(FanOutPolicy::FirstSuccess).execute(3).await.is_ok());
+    assert!(fan_out(FanOutPolicy::BestEffort).execute(5).await.is_ok());
+    assert_eq!(slow.executed.get(), vec![1, 2, 3, 5]);
+    assert_eq!(fast.executed.get(), vec![2]);
+
+    // The fan-out returns once the fast executor succeeds, while the slow one
+    // keeps running, and each of them reports its own outcome.
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    fan_out(FanOutPolicy::FirstSuccess)
+        .execute_with_outcomes(4, OutcomeReporter::new(sender).with_executor("relays"))
+        .await
+        .unwrap();
+    let submitted = |executor: &str| ActionOutcome {
+        action_id: 0,
+        executor: Some(executor.to_string()),
+        outcome: ExecutionOutcome::Submitted { tx_hashes: vec![] },
+    };
+    assert_eq!(fast.executed.get(), vec![2, 4]);
+    assert_eq!(receiver.recv().await, Some(submitted("relays/fast")));
+    assert_eq!(receiver.recv().await, Some(submitted("relays/slow")));
+    assert_eq!(slow.executed.get(), vec![1, 2, 3, 5, 4]);
+}
+
+/// Test that polling block and log collectors fetch new data without a
+/// subscription.
+#[tokio::test]
+async fn test_polling_collectors() {
+    // Mock responses are returned in reverse order.
+    let (provider, mock) = Provider::mocked();
+    let provider = Arc::new(provider);
+    let block = Block::<H256> {
+        hash: Some(H256::repeat_byte(2)),
+        parent_hash: H256::repeat_byte(1),
+        number: Some(U64::from(2)),
+        ..Default::default()
+    };
+    mock.push::<Block<H256>, _>(block).unwrap();
+    mock.push::<Vec<H256>, _>(vec![H256::repeat_byte(2)])
+        .unwrap();
+    mock.push::<U256, _>(U256::from(1)).unwrap();
+
+    let block_collector =
+        BlockCollector::new(provider.clone()).with_polling(Duration::from_millis(10));
+    let mut stream = block_collector.get_event_stream().await.unwrap();
+    let new_block = stream.next().await.unwrap();
+    assert_eq!(new_block.hash, H256::repeat_byte(2));
+    assert_eq!(new_block.parent_hash, H256::repeat_byte(1));
+    assert_eq!(new_block.number, U64::from(2));
+
+    let log = Log {
+        block_number: Some(U64::from(12)),
+        ..Default::default()
+    };
+    mock.push::<Vec<Log>, _>(vec![log.clone()]).unwrap();
+    mock.push::<U64, _>(U64::from(12)).unwrap();
+    mock.push::<U64, _>(U64::from(10)).unwrap();
+
+    let log_collector =
+        LogCollector::new(provider.clone(), Filter::new()).with_polling(Duration::from_millis(10));
+    let mut stream = log_collector.get_event_stream().await.unwrap();
+    assert_eq!(stream.next().await.unwrap(), log);
+}
+
+/// Test that a log collector backfills from its start block in chunks, which
+/// adapt to the ranges the provider accepts, and resumes where it left off.
+/// Fixed ranges of logs are fetched in the same chunks.
+#[tokio::test]
+async fn test_log_collector_backfills_in_chunks() {
+    let logs: Vec<Log> = (5..=10)
+        .map(|block| Log {
+            block_number: Some(U64::from(block)),
+            ..Default::default()
+        })
+        .collect();
+    let error = |message: &str| {
+        MockResponse::Error(JsonRpcError {
+            code: -32005,
+            message: message.to_string(),
+            data: None,
+        })
+    };
+
+    // Mock responses are returned in reverse order.
+    let (provider, mock) = Provider::mocked();
+    mock.push::<Vec<Log>, _>(logs[4..].to_vec()).unwrap();
+    mock.push_response(error("rate limit exceeded"));
+    mock.push::<U64, _>(U64::from(10)).unwrap();
+    mock.push::<Vec<Log>, _>(logs[3..5].to_vec()).unwrap();
+    for log in logs[..3].iter().rev() {
+        mock.push::<Vec<Log>, _>(vec![log.clone()]).unwrap();
+    }
+    mock.push_response(error("block range too large"));
+    mock.push::<U64, _>(U64::from(9)).unwrap();
+
+    let collector = LogCollector::new(Arc::new(provider), Filter::new())
+        .with_start_block(5)
+        .with_chunk_size(2)
+        .with_polling(Duration::from_millis(10));
+    let stream = collector.get_event_stream().await.unwrap();
+    let received: Vec<_> = stream.take(5).collect().await;
+    assert_eq!(received, logs[..5]);
+
+    // A restarted collector resumes after the last log it emitted.
+    let stream = collector.get_event_stream().await.unwrap();
+    let received: Vec<_> = stream.take(1).collect().await;
+    assert_eq!(received, logs[5..]);
+
+    // The chunk size is halved after the first query is rejected, and grows
+    // back after successful queries. Rate limiting errors are retried as is.
+    mock.assert_request("eth_blockNumber", ()).unwrap();
+    for (from_block, to_block) in [(5, 6), (5, 5), (6, 6), (7, 7), (8, 9)] {
+        let filter = Filter::new().from_block(from_block).to_block(to_block);
+        mock.assert_request("eth_getLogs", [filter]).unwrap();
+    }
+    mock.assert_request("eth_blockNumber", ()).unwrap();
+    for (from_block, to_block) in [(9, 10), (9, 10)] {
+        let filter = Filter::new().from_block(from_block).to_block(to_block);
+        mock.assert_request("eth_getLogs", [filter]).unwrap();
+    }
+
+    // Logs of a fixed range are fetched in the same adaptive chunks.
+    for chunk in logs.chunks(2).rev() {
+        mock.push::<Vec<Log>, _>(chunk.to_vec()).unwrap();
+    }
+    mock.push_response(error("query returned more than 10000 results"));
+    let fetched = LogCollector::new(Arc::new(Provider::new(mock.clone())), Filter::new())
+        .with_chunk_size(4)
+        .get_logs(5, 10)
+        .await
+        .unwrap();
+    assert_eq!(fetched, logs);
+    for (from_block, to_block) in [(5, 8), (5, 6), (7, 8), (9, 10)] {
+        let filter = Filter::new().from_block(from_block).to_block(to_block);
+        mock.assert_request("eth_getLogs", [filter]).unwrap();
+    }
+}
+
+/// Test that a mempool filter matches transactions by recipient, selector,
+/// value and gas price, and that calldata is decoded against registered ABIs.
+#[tokio::test]
+async fn test_mempool_filter_and_decoder() {
+    let abi = parse_abi(&["function transfer(address to, uint256 amount)"]).unwrap();
+    let transfer = abi.function("transfer").unwrap();
+    let router = Address::repeat_byte(1);
+    let args = vec![Token::Address(router), Token::Uint(U256::from(7))];
+    let tx = Transaction {
+        to: Some(router),
+        input: transfer.encode_input(&args).unwrap().into(),
+        value: U256::from(100),
+        gas_price: Some(U256::from(30)),
+        ..Default::default()
+    };
+
+    let filter = MempoolFilter::new()
+        .with_to([router])
+        .with_selectors([transfer.short_signature()])
+        .with_min_value(U256::from(100))
+        .with_min_gas_price(U256::from(30));
+    assert!(filter.matches(&tx));
+    assert!(MempoolFilter::new().matches(&tx));
+    assert!(!filter
+        .clone()
+        .with_to([Address::zero()])
+        .matches(&Transaction {
+            to: None,
+            ..tx.clone()
+        }));
+    assert!(!filter.matches(&Transaction {
+        input: Default::default(),
+        ..tx.clone()
+    }));
+    assert!(!filter.clone().with_min_value(U256::from(101)).matches(&tx));
+    assert!(!filter.with_min_gas_price(U256::from(31)).matches(&tx));
+
+    let decoder = CallDecoder::new().with_abi(&abi);
+    let call = decoder.decode(&tx.input).unwrap();
+    assert_eq!(call.name, "transfer");
+    assert_eq!(call.signature, "transfer(address,uint256)");
+    assert_eq!(call.inputs, args);
+    assert_eq!(decoder.decode(&[0xde, 0xad, 0xbe, 0xef]), None);
+}
+
+/// Test that OpenSea stream payloads are mapped to the events they carry, and
+/// that payloads other than orders and sales are skipped.
+#[tokio::test]
+async fn test_opensea_event_from_payload() {
+    let nft: Address = "0x5af0d9827e0c53e4799bb226655a1de152a425a5"
+        .parse()
+        .unwrap();
+    let order_hash = H256::repeat_byte(7);
+    let timestamp = "2023-05-01T12:00:00.000000+00:00";
+    let item = json!({
+        "nft_id": format!("ethereum/{:?}/42", nft),
+        "permalink": format!("https://opensea.io/assets/ethereum/{:?}/42", nft),
+        "metadata": {
+            "name": "Milady 42",
+            "description": null,
+            "image_url": null,
+            "animation_url": null,
+            "metadata_url": null
+        },
+        "chain": { "name": "ethereum" }
+    });
+    let payment_token = json!({
+        "address": Address::zero(),
+        "decimals": 18,
+        "eth_price": "1.000000000000000",
+        "name": "Ether",
+        "symbol": "ETH",
+        "usd_price": "1850.000000000000000"
+    });
+    let transaction = json!({ "hash": H256::repeat_byte(9), "timestamp": timestamp });
+    let account = |byte: u8| json!({ "address": Address::repeat_byte(byte) });
+    let event = |payload: serde_json::Value| {
+        OpenseaEvent::from_payload(serde_json::from_value(payload).unwrap())
+    };
+
+    let listing = event(json!({
+        "event_type": "item_listed",
+        "payload": {
+            "event_timestamp": timestamp,
+            "collection": { "slug": "milady" },
+            "item": item,
+            "base_price": "1500000000000000000",
+            "expiration_date": "2023-06-01T12:00:00.000000+00:00",
+            "is_private": false,
+            "listing_date": timestamp,
+            "listing_type": null,
+            "maker": account(1),
+            "taker": null,
+            "payment_token": payment_token,
+            "quantity": 1,
+            "order_hash": order_hash
+        }
+    }));
+    let Some(OpenseaEvent::Listing(listing)) = listing else {
+        panic!("expected a listing, got {:?}", listing);
+    };
+    assert_eq!(listing.base_price, U256::exp10(18) * 3 / 2);
+    assert_eq!(listing.order_hash, order_hash);
+    assert_eq!(listing.payment_token.address, Address::zero());
+    assert_eq!(listing.context.item.nft_id.address, nft);
+    assert_eq!(listing.context.item.nft_id.id, U256::from(42));
+
+    let sale = event(json!({
+        "event_type": "item_sold",
+        "payload": {
+            "event_timestamp": timestamp,
+            "collection": { "slug": "milady" },
+            "item": item,
+            "closing_date": timestamp,
+            "is_private": false,
+            "listing_type": null,
+            "maker": account(1),
+            "taker": account(2),
+            "payment_token": payment_token,
+            "quantity": 1,
+            "sale_price": "2000000000000000000",
+            "order_hash": order_hash,
+            "transaction": transaction
+        }
+    }));
+    let Some(OpenseaEvent::Sale(sale)) = sale else {
+        panic!("expected a sale, got {:?}", sale);
+    };
+    assert_eq!(sale.sale_price, U256::exp10(18) * 2);
+    assert_eq!(sale.context.item.nft_id.id, U256::from(42));
+
+    let cancellation = event(json!({
+        "event_type": "item_cancelled",
+        "payload": {
+            "event_timestamp": timestamp,
+            "collection": { "slug": "milady" },
+            "item": item,
+            "listing_type": null,
+            "payment_token": payment_token,
+            "quantity": 1,
+            "order_hash": order_hash,
+            "transaction": transaction
+        }
+    }));
+    let Some(OpenseaEvent::Cancellation(cancellation)) = cancellation else {
+        panic!("expected a cancellation, got {:?}", cancellation);
+    };
+    assert_eq!(cancellation.context.item.nft_id.address, nft);
+
+    let transfer = event(json!({
+        "event_type": "item_transferred",
+        "payload": {
+            "event_timestamp": timestamp,
+            "collection": { "slug": "milady" },
+            "item": item,
+            "from_account": account(1),
+            "to_account": account(2),
+            "quantity": 1,
+            "transaction": transaction
+        }
+    }));
+    assert!(transfer.is_none());
+}
+
+/// Test that an OpenSea order collector listens to every collection on mainnet
+/// by default, and only to the given collections and network otherwise.
+#[tokio::test]
+async fn test_opensea_order_collector_subscriptions() {
+    let collector = OpenseaOrderCollector::new("key".into());
+    assert!(matches!(collector.network(), Network::Mainnet));
+    assert!(matches!(collector.subscriptions()[..], [Collection::All]));
+
+    let collector = collector
+        .with_network(Network::Testnet)
+        .with_collections(["milady", "boredapeyachtclub"]);
+    assert!(matches!(collector.network(), Network::Testnet));
+    let slugs: Vec<_> = collector
+        .subscriptions()
+        .into_iter()
+        .map(|collection| match collection {
+            Collection::Collection(slug) => slug,
+            other => panic!("expected a collection slug, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(slugs, ["milady", "boredapeyachtclub"]);
+}
+
+/// Test that MEV-Share hints decode the event kind, selectors and swaps.
+#[tokio::test]
+async fn test_mev_share_hint_decodes_swaps() {
+    let word = |value: I256| {
+        let mut bytes = [0u8; 32];
+        value.into_raw().to_big_endian(&mut bytes);
+        bytes
+    };
+    let v2_topic = H256(keccak256(
+        "Swap(address,uint256,uint256,uint256,uint256,address)",
+    ));
+    let v3_topic = H256(keccak256(
+        "Swap(address,address,int256,int256,uint160,uint128,int24)",
+    ));
+    let v2_data: Vec<u8> = [0, 5, 7, 0]
+        .into_iter()
+        .flat_map(|amount| word(I256::from(amount)))
+        .collect();
+    let v3_data: Vec<u8> = [10, -20, 1, 1, 1]
+        .into_iter()
+        .flat_map(|amount| word(I256::from(amount)))
+        .collect();
+    let event = Event {
+        hash: H256::repeat_byte(1),
+        transactions: vec![EventTransaction {
+            to: None,
+            function_selector: Some(FunctionSelector([1, 2, 3, 4])),
+            calldata: None,
+        }],
+        logs: vec![
+            EventTransactionLog {
+                address: Address::repeat_byte(2),
+                topics: vec![v2_topic],
+                data: v2_data.into(),
+            },
+            EventTransactionLog {
+                address: Address::repeat_byte(3),
+                topics: vec![v3_topic],
+                data: v3_data.into(),
+            },
+            EventTransactionLog {
+                address: Address::repeat_byte(4),
+                topics: vec![v2_topic],
+                data: Default::default(),
+            },
+        ],
+    };
+
+    let hint = MevShareHint::from(event.clone());
+    assert_eq!(hint.kind, HintKind::Transaction);
+    assert_eq!(hint.selectors, vec![[1, 2, 3, 4]]);
+    assert_eq!(
+        hint.swaps,
+        vec![
+            SwapHint {
+                pool: Address::repeat_byte(2),
+                protocol: SwapProtocol::UniswapV2,
+                zero_for_one: Some(false),
+                amount_in: Some(U256::from(5)),
+                amount_out: Some(U256::from(7)),
+            },
+            SwapHint {
+                pool: Address::repeat_byte(3),
+                protocol: SwapProtocol::UniswapV3,
+                zero_for_one: Some(true),
+                amount_in: Some(U256::from(10)),
+                amount_out: Some(U256::from(20)),
+            },
+            SwapHint {
+                pool: Address::repeat_byte(4),
+                protocol: SwapProtocol::UniswapV2,
+                zero_for_one: None,
+                amount_in: None,
+                amount_out: None,
+            },
+        ]
+    );
+
+    let bundle = Event {
+        transactions: vec![event.transactions[0].clone(); 2],
+        ..event
+    };
+    assert_eq!(MevShareHint::from(bundle).kind, HintKind::Bundle);
+}
+
+/// Test that a txpool collector reports added, replaced and evicted
+/// transactions between snapshots.
+#[tokio::test]
+async fn test_txpool_collector_diffs_snapshots() {
+    let tx = |sender: u8, nonce: u64, hash: u8| Transaction {
+        hash: H256::repeat_byte(hash),
+        from: Address::repeat_byte(sender),
+        nonce: U256::from(nonce),
+        ..Default::default()
+    };
+
+    // Mock responses are returned in reverse order.
+    let (provider, mock) = Provider::mocked();
+    mock.push::<Vec<Transaction>, _>(vec![tx(1, 1, 3), tx(2, 0, 4)])
+        .unwrap();
+    mock.push::<Vec<Transaction>, _>(vec![tx(1, 1, 2), tx(1, 0, 1)])
+        .unwrap();
+
+    let collector = TxpoolCollector::new(Arc::new(provider), Duration::from_millis(10))
+        .with_api(TxpoolApi::Parity);
+    let stream = collector.get_event_stream().await.unwrap();
+    let events: Vec<_> = stream.take(5).collect().await;
+    assert_eq!(
+        events,
+        vec![
+            TxpoolEvent::Added(tx(1, 0, 1)),
+            TxpoolEvent::Added(tx(1, 1, 2)),
+            TxpoolEvent::Replaced {
+                old: tx(1, 1, 2),
+                new: tx(1, 1, 3),
+            },
+            TxpoolEvent::Added(tx(2, 0, 4)),
+            TxpoolEvent::Evicted(tx(1, 0, 1)),
+        ]
+    );
+}
+
+/// Test that an oracle collector normalises the answers of its feeds, and flags
+/// stale answers.
+#[tokio::test]
+async fn test_oracle_collector_emits_price_updates() {
+    let aggregator = Address::repeat_byte(1);
+    let token = Address::repeat_byte(2);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let answer_updated = |answer: i64, round_id: u64, updated_at: u64| {
+        let word = |value: U256| {
+            let mut bytes = [0u8; 32];
+            value.to_big_endian(&mut bytes);
+            bytes
+        };
+        Log {
+            address: aggregator,
+            topics: vec![
+                H256(keccak256("AnswerUpdated(int256,uint256,uint256)")),
+                H256(word(I256::from(answer).into_raw())),
+                H256(word(U256::from(round_id))),
+            ],
+            data: word(U256::from(updated_at)).to_vec().into(),
+            ..Default::default()
+        }
+    };
+    let logs = vec![
+        answer_updated(200012345678, 1, now - 7200),
+        answer_updated(-1, 2, now),
+        answer_updated(200100000000, 3, now),
+    ];
+
+    // Mock responses are returned in reverse order.
+    let (provider, mock) = Provider::mocked();
+    mock.push::<Vec<Log>, _>(logs).unwrap();
+    mock.push::<U64, _>(U64::from(11)).unwrap();
+    mock.push::<U64, _>(U64::from(10)).unwrap();
+
+    let feed = PriceFeed::new(aggregator, token, 8);
+    let collector =
+        OracleCollector::new(Arc::new(provider), [feed]).with_polling(Duration::from_millis(10));
+    let stream = collector.get_event_stream().await.unwrap();
+    let updates: Vec<_> = stream.take(2).collect().await;
+    assert_eq!(
+        updates,
+        vec![
+            PriceUpdate {
+                token,
+                price: 2000.12345678,
+                round_id: U256::from(1),
+                updated_at: now - 7200,
+                stale: true,
+            },
+            PriceUpdate {
+                token,
+                price: 2001.0,
+                round_id: U256::from(3),
+                updated_at: now,
+                stale: false,
+            },
+        ]
+    );
+}
+
+/// Test that interval collector emits sequenced ticks at its cadence.
+#[tokio::test]
+async fn test_interval_collector_emits_ticks() {
+    let collector =
+        IntervalCollector::new(Duration::from_millis(20)).with_jitter(Duration::from_millis(5));
+    let stream = collector.get_event_stream().await.unwrap();
+
+    let started = Instant::now();
+    let ticks: Vec<_> = stream.take(3).collect().await;
+    assert!(started.elapsed() >= Duration::from_millis(40));
+    assert_eq!(
+        ticks.iter().map(|tick| tick.seq).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+    assert!(ticks[0].at < ticks[2].at);
+}
+
+/// Test that the engine builder names components and describes how they are
+/// wired together.
+#[tokio::test]
+async fn test_engine_builder_describes_topology() {
+    let engine: Engine<u64, u64> = Engine::builder()
+        .collector_with_options(
+            "numbers",
+            Box::new(MockCollector { events: vec![1] }),
+            CollectorOptions::default().with_tag("mock"),
+        )
+        .strategy("double", Box::new(MockStrategy))
+        .strategy_with_options(
+            "forward",
+            Box::new(RetryingStrategy::default()),
+            StrategyOptions::default().with_executor("small"),
+        )
+        .executor_with_options(
+            "small",
+            Box::new(MockExecutor::default()),
+            ExecutorOptions::default().with_filter(|action| *action < 5),
+        )
+        .executor("all", Box::new(MockExecutor::default()))
+        .build();
+
+    assert_eq!(
+        engine.describe().to_string(),
+        "collectors:\n  numbers [mock]\n\
+         strategies:\n  double: numbers -> small, all\n  forward: numbers -> small\n\
+         executors:\n  small (filtered): double, forward\n  all: double"
+    );
+
+    let summary = engine.run().await.unwrap().shutdown().await;
+    assert_eq!(summary.tasks_failed, 0);
+}
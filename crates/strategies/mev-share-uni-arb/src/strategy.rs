@@ -2,19 +2,22 @@ use std::collections::HashMap;
 use std::ops::Add;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
 
 use anyhow::Result;
-use artemis_core::types::Strategy;
+use artemis_core::collectors::block_collector::NewBlock;
+use artemis_core::types::{Snapshot, StateSnapshot, Strategy};
 
 use ethers::signers::Signer;
 
 use ethers::providers::Middleware;
-use ethers::types::{Address, H256};
+use ethers::types::{Address, H256, U64};
 use ethers::types::{H160, U256};
 use mev_share::rpc::{BundleItem, Inclusion, SendBundleRequest};
-use tracing::info;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
 
 use crate::types::V2V3PoolRecord;
 
@@ -22,8 +25,12 @@ use super::types::{Action, Event};
 
 use mev_share_bindings::blind_arb::BlindArb;
 
+/// Maximum age of the latest block received for its number to be used as the
+/// current block, i.e. two slots.
+const MAX_BLOCK_AGE: Duration = Duration::from_secs(24);
+
 /// Information about a uniswap v2 pool.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct V2PoolInfo {
     /// Address of the v2 pool.
     pub v2_pool: H160,
@@ -41,6 +48,11 @@ pub struct MevShareUniArb<M, S> {
     tx_signer: S,
     /// Arb contract.
     arb_contract: BlindArb<M>,
+    /// Block at which the pool map was last loaded.
+    synced_block: Option<u64>,
+    /// Latest block received, used to set the target block of bundles while it
+    /// is recent.
+    latest_block: Option<NewBlock>,
 }
 
 impl<M: Middleware + 'static, S: Signer> MevShareUniArb<M, S> {
@@ -51,6 +63,8 @@ impl<M: Middleware + 'static, S: Signer> MevShareUniArb<M, S> {
             pool_map: HashMap::new(),
             tx_signer: signer,
             arb_contract: BlindArb::new(arb_contract_address, client),
+            synced_block: None,
+            latest_block: None,
         }
     }
 }
@@ -60,8 +74,15 @@ impl<M: Middleware + 'static, S: Signer + 'static> Strategy<Event, Action>
     for MevShareUniArb<M, S>
 {
     /// Initialize the strategy. This is called once at startup, and loads
-    /// pool information into memory.
+    /// pool information into memory, unless it was restored from a snapshot.
     async fn sync_state(&mut self) -> Result<()> {
+        let current_block = self.client.get_block_number().await?.as_u64();
+        if self.synced_block.is_some() {
+            info!("pool information restored from snapshot, skipping csv");
+            self.synced_block = Some(current_block);
+            return Ok(());
+        }
+
         // Read pool information from csv file.
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push("resources/v3_v2_pools.csv");
@@ -78,6 +99,7 @@ impl<M: Middleware + 'static, S: Signer + 'static> Strategy<Event, Action>
                 },
             );
         }
+        self.synced_block = Some(current_block);
 
         Ok(())
     }
@@ -101,23 +123,58 @@ impl<M: Middleware + 'static, S: Signer + 'static> Strategy<Event, Action>
                     "Found a v3 pool match at address {:?}, submitting bundles",
                     address
                 );
-                self.generate_bundles(address, event.hash)
-                    .await
-                    .into_iter()
-                    .map(Action::SubmitBundle)
-                    .collect()
+                match self.generate_bundles(address, event.hash).await {
+                    Ok(bundles) => bundles.into_iter().map(Action::SubmitBundle).collect(),
+                    Err(e) => {
+                        error!("Error generating bundles: {}", e);
+                        vec![]
+                    }
+                }
+            }
+            Event::NewBlock(block) => {
+                self.latest_block = Some(block);
+                vec![]
             }
         }
     }
 }
 
+/// Snapshots of the pool map let the strategy restart without reading the csv.
+impl<M, S> Snapshot for MevShareUniArb<M, S> {
+    type State = HashMap<H160, V2PoolInfo>;
+
+    fn snapshot(&self) -> Option<StateSnapshot<Self::State>> {
+        Some(StateSnapshot {
+            block_number: self.synced_block?,
+            state: self.pool_map.clone(),
+        })
+    }
+
+    fn restore(&mut self, snapshot: StateSnapshot<Self::State>) {
+        self.pool_map = snapshot.state;
+        self.synced_block = Some(snapshot.block_number);
+    }
+}
+
 impl<M: Middleware + 'static, S: Signer + 'static> MevShareUniArb<M, S> {
+    /// Returns the number of the current block: the latest block received if it
+    /// is recent, or else the block number reported by the node.
+    async fn current_block_number(&self) -> Result<U64> {
+        if let Some(block) = &self.latest_block {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            if now.saturating_sub(block.timestamp.as_u64()) <= MAX_BLOCK_AGE.as_secs() {
+                return Ok(block.number);
+            }
+        }
+        Ok(self.client.get_block_number().await?)
+    }
+
     /// Generate a series of bundles of varying sizes to submit to the matchmaker.
     pub async fn generate_bundles(
         &self,
         v3_address: H160,
         tx_hash: H256,
-    ) -> Vec<SendBundleRequest> {
+    ) -> Result<Vec<SendBundleRequest>> {
         let mut bundles = Vec::new();
         let v2_info = self.pool_map.get(&v3_address).unwrap();
 
@@ -142,8 +199,8 @@ impl<M: Middleware + 'static, S: Signer + 'static> MevShareUniArb<M, S> {
 
         // Set parameters for the backruns.
         let payment_percentage = U256::from(0);
-        let bid_gas_price = self.client.get_gas_price().await.unwrap();
-        let block_num = self.client.get_block_number().await.unwrap();
+        let bid_gas_price = self.client.get_gas_price().await?;
+        let block_num = self.current_block_number().await?;
 
         for size in sizes {
             let arb_tx = {
@@ -209,6 +266,6 @@ impl<M: Middleware + 'static, S: Signer + 'static> MevShareUniArb<M, S> {
             info!("submitting bundle: {:?}", bundle);
             bundles.push(bundle);
         }
-        bundles
+        Ok(bundles)
     }
 }
@@ -1,11 +1,14 @@
+use artemis_core::collectors::block_collector::NewBlock;
 use ethers::types::H160;
 
 use mev_share::{rpc::SendBundleRequest, sse};
+use serde::{Deserialize, Serialize};
 
 /// Core Event enum for the current strategy.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Event {
     MEVShareEvent(sse::Event),
+    NewBlock(NewBlock),
 }
 
 /// Core Action enum for the current strategy.
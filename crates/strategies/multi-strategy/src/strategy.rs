@@ -1,6 +1,7 @@
 use crate::types::{Action, ArbitragePath, Config, Metrics, PoolConfig, PoolReserves, PoolType, PriceUpdate, State, Swap};
 use anyhow::Result;
-use artemis_core::types::Strategy;
+use artemis_core::collectors::interval_collector::Tick;
+use artemis_core::types::{ActionOutcome, ExecutionOutcome, Strategy};
 use async_trait::async_trait;
 use ethers::core::types::{Address, Transaction, H256, U256};
 use ethers::middleware::SignerMiddleware;
@@ -10,6 +11,7 @@ use futures::future::join_all;
 use mev_share::sse::Event as MevShareEvent;
 use multi_strategy_bindings::flash_arb_executor::FlashArbExecutor;
 use multi_strategy_bindings::jit_liquidity_provider::JITLiquidityProvider;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
@@ -23,7 +25,7 @@ const GAS_COST_BASE: u64 = 250000; // Base gas cost for flash loan
 const GAS_PRICE_GWEI: f64 = 30.0; // Estimated gas price in gwei
 
 /// Event types that our strategy processes
-#[derive(Debug)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum Event {
     /// New block event
     NewBlock(Block<H256>),
@@ -33,6 +35,8 @@ pub enum Event {
     MevShareEvent(MevShareEvent),
     /// Price update event
     PriceUpdate(PriceUpdate),
+    /// Periodic tick, used for time-based housekeeping
+    Tick(Tick),
 }
 
 /// The multi-strategy implementation
@@ -751,7 +755,7 @@ impl<M: Middleware + 'static, S: Signer + 'static> MultiStrategy<M, S> {
                     let selector = &tx.input.0[0..4];
                     // Common swap function selectors
                     // 0x022c0d9f - swap (Uniswap V2)
-                    // 0x128acb08 - exactInputSingle (Uniswap V3 Router)
+                    // 0x128acb08 - swap (Uniswap V3 pool)
                     if selector == [0x02, 0x2c, 0x0d, 0x9f] || 
                        selector == [0x12, 0x8a, 0xcb, 0x08] {
                         potential_jit_pools.push((*pool_address, pool_reserve.clone()));
@@ -900,6 +904,10 @@ impl<M: Middleware + 'static, S: Signer + 'static> Strategy<Event, Action> for M
             Event::MevShareEvent(event) => self.process_mev_share(event).await,
             Event::Transaction(tx) => self.process_transaction(tx).await,
             Event::PriceUpdate(update) => self.update_prices(update).await,
+            Event::Tick(_) => {
+                self.update_expired_transactions();
+                Vec::new()
+            }
         }
     }
     
@@ -915,4 +923,31 @@ impl<M: Middleware + 'static, S: Signer + 'static> Strategy<Event, Action> for M
         
         Ok(())
     }
+    
+    /// Update metrics and tracked transactions once an action's outcome is known
+    async fn on_execution_outcome(&mut self, outcome: ActionOutcome) -> Vec<Action> {
+        match outcome.outcome {
+            ExecutionOutcome::Submitted { .. } => {}
+            ExecutionOutcome::Included { tx_hash, .. } => {
+                self.state.tracked_txs.remove(&tx_hash);
+                self.metrics.successful_txs += 1;
+            }
+            ExecutionOutcome::Reverted { tx_hash, .. } | ExecutionOutcome::Dropped { tx_hash } => {
+                self.state.tracked_txs.remove(&tx_hash);
+                self.metrics.failed_txs += 1;
+            }
+            ExecutionOutcome::BundleNotIncluded { tx_hashes, .. } => {
+                for tx_hash in tx_hashes {
+                    self.state.tracked_txs.remove(&tx_hash);
+                }
+                self.metrics.failed_txs += 1;
+            }
+            ExecutionOutcome::Failed { reason } => {
+                warn!("Action {} failed: {}", outcome.action_id, reason);
+                self.metrics.failed_txs += 1;
+            }
+        }
+        
+        Vec::new()
+    }
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use artemis_core::collectors::oracle_collector;
 use ethers::core::types::{Address, U256};
 use ethers::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -106,7 +107,7 @@ pub struct TrackedTransaction {
 }
 
 /// Price update information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PriceUpdate {
     /// Token that was updated
     pub token: Address,
@@ -114,6 +115,17 @@ pub struct PriceUpdate {
     pub price: f64,
 }
 
+/// Converts a price reported by an oracle collector, whose feed must be quoted
+/// in ETH. Stale prices should be filtered out beforehand.
+impl From<oracle_collector::PriceUpdate> for PriceUpdate {
+    fn from(update: oracle_collector::PriceUpdate) -> Self {
+        Self {
+            token: update.token,
+            price: update.price,
+        }
+    }
+}
+
 /// Type of opportunity
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OpportunityType {
@@ -7,26 +7,28 @@ use async_trait::async_trait;
 use bindings::lssvm_pair_factory::{LSSVMPairFactory, NewPairFilter};
 use bindings::sudo_opensea_arb::SudoOpenseaArb;
 use bindings::sudo_pair_quoter::{SellQuote, SudoPairQuoter, SUDOPAIRQUOTER_DEPLOYED_BYTECODE};
-use tracing::info;
+use tracing::{debug, info};
 
 use crate::constants::FACTORY_DEPLOYMENT_BLOCK;
 use crate::types::Config;
 use anyhow::Result;
 use artemis_core::collectors::block_collector::NewBlock;
-use artemis_core::collectors::opensea_order_collector::OpenseaOrder;
+use artemis_core::collectors::log_collector::LogCollector;
+use artemis_core::collectors::opensea_order_collector::OpenseaEvent;
 use artemis_core::executors::mempool_executor::{GasBidInfo, SubmitTxToMempool};
-use artemis_core::types::Strategy;
+use artemis_core::types::{Snapshot, StateSnapshot, Strategy, StrategyFault};
 use artemis_core::utilities::state_override_middleware::StateOverrideMiddleware;
+use ethers::contract::parse_log;
 use ethers::providers::Middleware;
 use ethers::types::{Filter, H256};
 use ethers::types::{H160, U256};
-use opensea_stream::schema::Chain;
+use opensea_stream::schema::{Chain, ItemListedData};
 use opensea_v2::client::OpenSeaV2Client;
 
 use super::constants::{LSSVM_PAIR_FACTORY_ADDRESS, POOL_EVENT_SIGNATURES};
 use super::types::{
     fulfill_listing_response_to_basic_order_parameters, hash_to_fulfill_listing_request, Action,
-    Event,
+    Event, PoolState,
 };
 
 #[derive(Debug, Clone)]
@@ -47,6 +49,10 @@ pub struct OpenseaSudoArb<M> {
     pool_bids: HashMap<H160, U256>,
     /// Amount of profits to bid in gas
     bid_percentage: u64,
+    /// Last block the pool state is in sync with.
+    synced_block: Option<u64>,
+    /// Fault to report to the engine, set when the internal state is out of sync.
+    fault: Option<StrategyFault>,
 }
 
 impl<M: Middleware + 'static> OpenseaSudoArb<M> {
@@ -74,6 +80,8 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
             sudo_pools: HashMap::new(),
             pool_bids: HashMap::new(),
             bid_percentage: config.bid_percentage,
+            synced_block: None,
+            fault: None,
         }
     }
 }
@@ -81,21 +89,34 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
 #[async_trait]
 impl<M: Middleware + 'static> Strategy<Event, Action> for OpenseaSudoArb<M> {
     // In order to sync this strategy, we need to get the current bid for all Sudo pools.
+    // If the pool state was restored from a snapshot, we only need to catch up on the
+    // pools created or touched since then.
     async fn sync_state(&mut self) -> Result<()> {
-        // Block in which the pool factory was deployed.
-        let start_block = FACTORY_DEPLOYMENT_BLOCK;
+        // Block in which the pool factory was deployed, or after the last synced block.
+        let start_block = self
+            .synced_block
+            .map_or(FACTORY_DEPLOYMENT_BLOCK, |block| block + 1);
 
         let current_block = self.client.get_block_number().await?.as_u64();
 
         // Get all Sudo pool addresses deployed in the block range.
-        let pool_addresses = self.get_new_pools(start_block, current_block).await?;
+        let mut pool_addresses = self.get_new_pools(start_block, current_block).await?;
         info!("found {} deployed sudo pools", pool_addresses.len());
 
+        // Get existing pools touched in the block range.
+        if self.synced_block.is_some() {
+            pool_addresses.extend(self.get_touched_pools(start_block, current_block).await?);
+            pool_addresses.sort();
+            pool_addresses.dedup();
+            info!("catching up on {} sudo pools", pool_addresses.len());
+        }
+
         // Get current bids for update state for all Sudo pools.
         for addresses in pool_addresses.chunks(200) {
             let quotes = self.get_quotes_for_pools(addresses.to_vec()).await?;
             self.update_internal_pool_state(quotes);
         }
+        self.synced_block = Some(current_block);
         info!(
             "done syncing state, found available pools for {} collections",
             self.sudo_pools.len()
@@ -107,33 +128,69 @@ impl<M: Middleware + 'static> Strategy<Event, Action> for OpenseaSudoArb<M> {
     // Process incoming events, seeing if we can arb new orders, and updating the internal state on new blocks.
     async fn process_event(&mut self, event: Event) -> Vec<Action> {
         match event {
-            Event::OpenseaOrder(order) => self
-                .process_order_event(*order)
-                .await
-                .map_or(vec![], |a| vec![a]),
+            // Only listings can be arbed against the pools.
+            Event::OpenseaEvent(event) => match *event {
+                OpenseaEvent::Listing(listing) => self
+                    .process_listing_event(listing)
+                    .await
+                    .map_or(vec![], |a| vec![a]),
+                _ => vec![],
+            },
             Event::NewBlock(block) => match self.process_new_block_event(block).await {
                 Ok(_) => vec![],
                 Err(e) => {
-                    panic!("Strategy is out of sync {}", e);
+                    // The block was not applied, so resync to catch up on the pools
+                    // touched since the last synced block.
+                    self.fault = Some(StrategyFault::resync(format!(
+                        "strategy is out of sync: {}",
+                        e
+                    )));
+                    vec![]
                 }
             },
         }
     }
+
+    fn take_fault(&mut self) -> Option<StrategyFault> {
+        self.fault.take()
+    }
+}
+
+/// Snapshots of the pool state let the strategy restart without rescanning
+/// every pool since the factory was deployed.
+impl<M> Snapshot for OpenseaSudoArb<M> {
+    type State = PoolState;
+
+    fn snapshot(&self) -> Option<StateSnapshot<PoolState>> {
+        Some(StateSnapshot {
+            block_number: self.synced_block?,
+            state: PoolState {
+                sudo_pools: self.sudo_pools.clone(),
+                pool_bids: self.pool_bids.clone(),
+            },
+        })
+    }
+
+    fn restore(&mut self, snapshot: StateSnapshot<PoolState>) {
+        self.sudo_pools = snapshot.state.sudo_pools;
+        self.pool_bids = snapshot.state.pool_bids;
+        self.synced_block = Some(snapshot.block_number);
+    }
 }
 
 impl<M: Middleware + 'static> OpenseaSudoArb<M> {
-    // Process new orders as they come in.
-    async fn process_order_event(&mut self, event: OpenseaOrder) -> Option<Action> {
-        let nft_address = event.listing.context.item.nft_id.address;
+    // Process new listings as they come in.
+    async fn process_listing_event(&mut self, listing: ItemListedData) -> Option<Action> {
+        let nft_address = listing.context.item.nft_id.address;
         info!("processing order event for address {}", nft_address);
 
         // Ignore orders that are not on Ethereum.
-        match event.listing.context.item.nft_id.network {
+        match listing.context.item.nft_id.network {
             Chain::Ethereum => {}
             _ => return None,
         }
         // Ignore orders with non-eth payment.
-        if event.listing.payment_token.address != H160::zero() {
+        if listing.payment_token.address != H160::zero() {
             return None;
         }
 
@@ -145,12 +202,12 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
             .max_by(|a, b| a.1.cmp(b.1))?;
 
         // Ignore orders that are not profitable.
-        if max_bid <= &event.listing.base_price {
+        if max_bid <= &listing.base_price {
             return None;
         }
 
         // Build arb tx.
-        self.build_arb_tx(event.listing.order_hash, *max_pool, *max_bid)
+        self.build_arb_tx(listing.order_hash, *max_pool, *max_bid)
             .await
     }
 
@@ -170,6 +227,7 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
             .get_quotes_for_pools([new_pools, touched_pools].concat())
             .await?;
         self.update_internal_pool_state(quotes);
+        self.synced_block = Some(event.number.as_u64());
         Ok(())
     }
 
@@ -231,10 +289,10 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
             // If a quote is available, update both the pool_bids and the sudo_pools maps.
             if quote.quote_available {
                 self.pool_bids.insert(pool_address, quote.price);
-                self.sudo_pools
-                    .entry(quote.nft_address)
-                    .or_insert(vec![])
-                    .push(pool_address);
+                let pools = self.sudo_pools.entry(quote.nft_address).or_insert(vec![]);
+                if !pools.contains(&pool_address) {
+                    pools.push(pool_address);
+                }
             }
             // If a quote is unavailable, remove from both the pool_bids and the sudo_pools maps.
             else {
@@ -246,46 +304,39 @@ impl<M: Middleware + 'static> OpenseaSudoArb<M> {
         }
     }
 
-    /// Find all pools that were touched in a given block range.
+    /// Find all pools that were touched in a given block range. The range is
+    /// queried in chunks which adapt to the limits of the provider.
     async fn get_touched_pools(&self, from_block: u64, to_block: u64) -> Result<Vec<H160>> {
         let address_list = self.pool_bids.keys().cloned().collect::<Vec<_>>();
         let filter = Filter::new()
-            .from_block(from_block)
-            .to_block(to_block)
             .address(address_list)
             .events(&*POOL_EVENT_SIGNATURES);
 
-        let events = self.client.get_logs(&filter).await?;
+        let events = LogCollector::new(self.client.clone(), filter)
+            .get_logs(from_block, to_block)
+            .await?;
         let touched_pools = events.iter().map(|event| event.address).collect::<Vec<_>>();
         Ok(touched_pools)
     }
 
-    /// Find all pools that were created in a given block range.
+    /// Find all pools that were created in a given block range. The range is
+    /// queried in chunks which adapt to the limits of the provider.
     async fn get_new_pools(&self, from_block: u64, to_block: u64) -> Result<Vec<H160>> {
-        let mut pool_addresses = vec![];
-
-        // Maxium range for a single Alchemy query is 2000 blocks.
-        for block in (from_block..to_block).step_by(2000) {
-            let events = self
-                .lssvm_pair_factory
-                .event::<NewPairFilter>()
-                .from_block(block)
-                .to_block(block + 2000)
-                .query()
-                .await?;
-
-            let addresses = events
-                .iter()
-                .map(|event| event.pool_address)
-                .collect::<Vec<_>>();
-
-            info!(
-                "found {} new pools in block range, total progress: {}%",
-                addresses.len(),
-                100 * (block - from_block) / (to_block - from_block)
-            );
-            pool_addresses.extend(addresses);
-        }
+        let filter = self.lssvm_pair_factory.event::<NewPairFilter>().filter;
+        let events = LogCollector::new(self.client.clone(), filter)
+            .get_logs(from_block, to_block)
+            .await?;
+
+        let pool_addresses = events
+            .into_iter()
+            .map(|event| Ok(parse_log::<NewPairFilter>(event)?.pool_address))
+            .collect::<Result<Vec<_>>>()?;
+        debug!(
+            "found {} new pools in blocks {} to {}",
+            pool_addresses.len(),
+            from_block,
+            to_block
+        );
         Ok(pool_addresses)
     }
 }
@@ -1,18 +1,20 @@
 use artemis_core::{
-    collectors::{block_collector::NewBlock, opensea_order_collector::OpenseaOrder},
+    collectors::{block_collector::NewBlock, opensea_order_collector::OpenseaEvent},
     executors::mempool_executor::SubmitTxToMempool,
 };
 use bindings::zone_interface::{AdditionalRecipient, BasicOrderParameters};
-use ethers::types::{Chain, H160, H256};
+use ethers::types::{Chain, H160, H256, U256};
 use opensea_v2::types::{
     FulfillListingRequest, FulfillListingResponse, Fulfiller, Listing, ProtocolVersion,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Core Event enum for the current strategy.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Event {
     NewBlock(NewBlock),
-    OpenseaOrder(Box<OpenseaOrder>),
+    OpenseaEvent(Box<OpenseaEvent>),
 }
 
 /// Core Action enum for the current strategy.
@@ -28,6 +30,15 @@ pub struct Config {
     pub bid_percentage: u64,
 }
 
+/// Pool state of the strategy, which is saved in snapshots.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PoolState {
+    /// Map NFT addresses to a list of Sudo pair addresses which trade that NFT.
+    pub sudo_pools: HashMap<H160, Vec<H160>>,
+    /// Map Sudo pool addresses to the current bid for that pool (in ETH).
+    pub pool_bids: HashMap<H160, U256>,
+}
+
 /// Convenience function to convert a hash to a fulfill listing request
 pub fn hash_to_fulfill_listing_request(hash: H256) -> FulfillListingRequest {
     FulfillListingRequest {
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use artemis_core::{
-    collectors::mevshare_collector::MevShareCollector,
+    collectors::{block_collector::BlockCollector, mevshare_collector::MevShareCollector},
     engine::Engine,
     executors::mev_share_executor::MevshareExecutor,
     types::{CollectorMap, ExecutorMap},
@@ -65,12 +65,15 @@ async fn main() -> Result<()> {
     let mut engine: Engine<Event, Action> = Engine::default();
 
     // Set up collector.
-    let mevshare_collector = Box::new(MevShareCollector::new(String::from(
-        "https://mev-share.flashbots.net",
-    )));
+    let mevshare_collector = Box::new(MevShareCollector::mainnet());
     let mevshare_collector = CollectorMap::new(mevshare_collector, Event::MEVShareEvent);
     engine.add_collector(Box::new(mevshare_collector));
 
+    // Set up block collector, which provides the bundle parameters.
+    let block_collector = Box::new(BlockCollector::new(provider.clone()));
+    let block_collector = CollectorMap::new(block_collector, Event::NewBlock);
+    engine.add_collector(Box::new(block_collector));
+
     // Set up strategy.
     let strategy = MevShareUniArb::new(
         Arc::new(provider.clone()),
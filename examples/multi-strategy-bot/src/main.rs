@@ -2,96 +2,22 @@ use anyhow::Result;
 use artemis_core::engine::Engine;
 use artemis_core::executors::mev_share_executor::{MevShareExecutor, SubmitToMevShare};
 use artemis_core::collectors::block_collector::BlockCollector;
-use artemis_core::collectors::mempool_collector::MempoolCollector;
+use artemis_core::collectors::interval_collector::{IntervalCollector, Tick};
+use artemis_core::collectors::mempool_collector::{MempoolCollector, MempoolFilter};
 use artemis_core::collectors::mevshare_collector::MevShareCollector;
-use artemis_core::types::{Collector, Executor, Strategy};
+use artemis_core::types::{CollectorExt, Executor, StrategyMap};
 use clap::Parser;
 use ethers::middleware::SignerMiddleware;
 use ethers::prelude::*;
 use ethers::providers::{Provider, Ws};
 use ethers::signers::{LocalWallet, Signer};
-use futures::stream::StreamExt;
 use multi_strategy::{Event as MultiStrategyEvent, Config, Action as MultiStrategyAction, MultiStrategy};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 use tracing_subscriber::FmtSubscriber;
 
-/// Collector adapter that converts from one event type to another
-struct CollectorAdapter<C, E1, E2> {
-    inner: C,
-    _phantom: std::marker::PhantomData<(E1, E2)>,
-}
-
-impl<C, E1, E2> CollectorAdapter<C, E1, E2>
-where
-    C: Collector<E1>,
-    E1: 'static,
-    E2: From<E1> + 'static,
-{
-    fn new(collector: C) -> Self {
-        Self {
-            inner: collector,
-            _phantom: std::marker::PhantomData,
-        }
-    }
-}
-
-#[async_trait::async_trait]
-impl<C, E1, E2> Collector<E2> for CollectorAdapter<C, E1, E2>
-where
-    C: Collector<E1>,
-    E1: 'static,
-    E2: From<E1> + 'static,
-{
-    async fn get_event_stream(&self) -> anyhow::Result<artemis_core::types::CollectorStream<'_, E2>> {
-        let stream = self.inner.get_event_stream().await?;
-        let mapped_stream = stream.map(E2::from);
-        Ok(Box::pin(mapped_stream))
-    }
-}
-
-/// Strategy adapter that converts from one event/action type to another
-struct StrategyAdapter<S, E1, E2, A1, A2> {
-    inner: S,
-    _phantom: std::marker::PhantomData<(E1, E2, A1, A2)>,
-}
-
-impl<S, E1, E2, A1, A2> StrategyAdapter<S, E1, E2, A1, A2>
-where
-    S: Strategy<E1, A1>,
-    E1: 'static,
-    E2: Into<E1> + 'static,
-    A1: 'static,
-    A2: From<A1> + 'static,
-{
-    fn new(strategy: S) -> Self {
-        Self {
-            inner: strategy,
-            _phantom: std::marker::PhantomData,
-        }
-    }
-}
-
-#[async_trait::async_trait]
-impl<S, E1, E2, A1, A2> Strategy<E2, A2> for StrategyAdapter<S, E1, E2, A1, A2>
-where
-    S: Strategy<E1, A1>,
-    E1: 'static,
-    E2: Into<E1> + 'static,
-    A1: 'static,
-    A2: From<A1> + 'static,
-{
-    async fn process_event(&mut self, event: E2) -> Vec<A2> {
-        let actions = self.inner.process_event(event.into()).await;
-        actions.into_iter().map(A2::from).collect()
-    }
-
-    async fn sync_state(&mut self) -> anyhow::Result<()> {
-        self.inner.sync_state().await
-    }
-}
-
 /// Multi-strategy MEV bot for arbitrage, JIT liquidity, and backrunning
 #[derive(Parser, Debug)]
 struct Args {
@@ -121,6 +47,7 @@ enum EngineEvent {
     Block(Block<H256>),
     Transaction(Transaction),
     MevShare(mev_share::sse::Event),
+    Tick(Tick),
 }
 
 impl From<Block<H256>> for EngineEvent {
@@ -141,12 +68,19 @@ impl From<mev_share::sse::Event> for EngineEvent {
     }
 }
 
+impl From<Tick> for EngineEvent {
+    fn from(tick: Tick) -> Self {
+        EngineEvent::Tick(tick)
+    }
+}
+
 impl From<EngineEvent> for MultiStrategyEvent {
     fn from(event: EngineEvent) -> Self {
         match event {
             EngineEvent::Block(block) => MultiStrategyEvent::NewBlock(block),
             EngineEvent::Transaction(tx) => MultiStrategyEvent::Transaction(tx),
             EngineEvent::MevShare(event) => MultiStrategyEvent::MevShareEvent(event),
+            EngineEvent::Tick(tick) => MultiStrategyEvent::Tick(tick),
         }
     }
 }
@@ -297,13 +231,28 @@ async fn main() -> Result<()> {
     let wallet = args.private_key.parse::<LocalWallet>()?.with_chain_id(chain_id);
     let provider = Arc::new(SignerMiddleware::new(provider, wallet.clone()));
     
-    // Create collectors with adapters
-    let block_collector = CollectorAdapter::new(BlockCollector::new(provider.clone()));
-    let mempool_collector = CollectorAdapter::new(MempoolCollector::new(provider.clone()));
+    // Create collectors, only keeping the swaps the JIT logic looks at, and
+    // dropping transactions seen again after a resubscription
+    let block_collector = BlockCollector::new(provider.clone()).map(EngineEvent::from);
+    let swap_filter = MempoolFilter::new().with_selectors([
+        [0x02, 0x2c, 0x0d, 0x9f], // swap (Uniswap V2)
+        [0x12, 0x8a, 0xcb, 0x08], // swap (Uniswap V3 pool)
+    ]);
+    let mempool_collector = MempoolCollector::new(provider.clone())
+        .with_filter(swap_filter)
+        .dedup_by_key(|tx: &Transaction| tx.hash, Duration::from_secs(60))
+        .map(EngineEvent::from);
     
-    // Create strategy with adapter
+    // Tick regularly so that expired submissions are cleaned up between blocks
+    let interval_collector = IntervalCollector::new(Duration::from_secs(5)).map(EngineEvent::from);
+    
+    // Create strategy, mapping engine events and strategy actions
     let strategy = MultiStrategy::new(config, provider.clone());
-    let strategy_adapter = StrategyAdapter::new(strategy);
+    let strategy_adapter = StrategyMap::new(
+        Box::new(strategy),
+        |event: EngineEvent| Some(MultiStrategyEvent::from(event)),
+        EngineAction::from,
+    );
     
     // Create executor with adapter
     let mev_share_executor = MevShareExecutor::new(
@@ -319,9 +268,11 @@ async fn main() -> Result<()> {
     // Add collectors to the engine
     engine.add_collector(Box::new(block_collector));
     engine.add_collector(Box::new(mempool_collector));
+    engine.add_collector(Box::new(interval_collector));
     
     if args.enable_mev_share {
-        let mev_share_collector = CollectorAdapter::new(MevShareCollector::new(args.mev_share_url.clone()));
+        let mev_share_collector =
+            MevShareCollector::new(args.mev_share_url.clone()).map(EngineEvent::from);
         engine.add_collector(Box::new(mev_share_collector));
     }
     